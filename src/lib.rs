@@ -1,4 +1,8 @@
-#![allow(clippy::precedence, clippy::many_single_char_names)]
+#![allow(
+    clippy::precedence,
+    clippy::many_single_char_names,
+    clippy::manual_div_ceil
+)]
 
 mod libs;
 pub use libs::*;
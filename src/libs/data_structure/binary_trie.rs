@@ -1,60 +1,252 @@
-pub struct BinaryTrie(Vec<[usize; 2]>);
-impl BinaryTrie {
+use crate::number::Unsigned;
+use std::marker::PhantomData;
+
+// multiset of T; every stored value is implicitly xored with a global mask
+pub struct BinaryTrie<T = u32> {
+    // children and the number of values below
+    nodes: Vec<([usize; 2], usize)>,
+    bits: usize,
+    xor: u64,
+    marker: PhantomData<T>,
+}
+impl<T: Unsigned> BinaryTrie<T> {
     pub fn new() -> Self {
-        Self(vec![[!0; 2]])
+        Self::with_bits(T::BITS)
+    }
+    // every value must be less than 2^bits
+    pub fn with_bits(bits: usize) -> Self {
+        assert!(bits <= T::BITS);
+        Self {
+            nodes: vec![([!0; 2], 0)],
+            bits,
+            xor: 0,
+            marker: PhantomData,
+        }
     }
-    fn height() -> usize {
-        8 * std::mem::size_of::<u32>()
+    pub fn len(&self) -> usize {
+        self.nodes[0].1
     }
-    pub fn add(&mut self, x: u32) -> bool {
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    fn fits(&self, x: u64) -> bool {
+        self.bits == 64 || x >> self.bits == 0
+    }
+    fn child(&self, i: usize, f: u64) -> Option<usize> {
+        let c = self.nodes[i].0[f as usize];
+        if c != !0 && self.nodes[c].1 > 0 {
+            Some(c)
+        } else {
+            None
+        }
+    }
+    fn cnt(&self, i: usize, f: u64) -> usize {
+        self.child(i, f).map(|c| self.nodes[c].1).unwrap_or(0)
+    }
+    pub fn count(&self, x: T) -> usize {
+        let x = x.to_u64();
+        if !self.fits(x) {
+            return 0;
+        }
+        let x = x ^ self.xor;
         let mut i = 0;
-        let mut added = false;
-        for j in (0..Self::height()).rev() {
-            let f = (x >> j & 1) as usize;
-            if self.0[i][f] > !0 >> 1 {
-                added = true;
-                self.0[i][f] = if self.0[i][f] == !0 {
-                    self.0.push([!0; 2]);
-                    self.0.len() - 1
-                } else {
-                    !self.0[i][f]
-                };
+        for j in (0..self.bits).rev() {
+            match self.child(i, x >> j & 1) {
+                Some(c) => i = c,
+                None => return 0,
             }
-            i = self.0[i][f];
         }
-        added
+        self.nodes[i].1
     }
-    pub fn remove(&mut self, x: u32) -> bool {
-        let mut path = vec![0; Self::height()];
+    // adds one copy of x; returns whether x was absent
+    pub fn add(&mut self, x: T) -> bool {
+        let x = x.to_u64();
+        assert!(self.fits(x));
+        let x = x ^ self.xor;
         let mut i = 0;
-        for j in (0..Self::height()).rev() {
-            path[j] = i;
+        self.nodes[0].1 += 1;
+        for j in (0..self.bits).rev() {
             let f = (x >> j & 1) as usize;
-            i = self.0[i][f];
-            if i > !0 >> 1 {
-                return false;
+            if self.nodes[i].0[f] == !0 {
+                self.nodes.push(([!0; 2], 0));
+                self.nodes[i].0[f] = self.nodes.len() - 1;
             }
+            i = self.nodes[i].0[f];
+            self.nodes[i].1 += 1;
         }
-        for j in 0..Self::height() {
-            let i = path[j];
-            let f = (x >> j & 1) as usize;
-            self.0[i][f] = !self.0[i][f];
-            if self.0[i][f ^ 1] <= !0 >> 1 {
-                break;
-            }
+        self.nodes[i].1 == 1
+    }
+    // removes one copy of x; returns whether x was present
+    pub fn remove(&mut self, x: T) -> bool {
+        if self.count(x) == 0 {
+            return false;
+        }
+        let x = x.to_u64() ^ self.xor;
+        let mut i = 0;
+        self.nodes[0].1 -= 1;
+        for j in (0..self.bits).rev() {
+            i = self.nodes[i].0[(x >> j & 1) as usize];
+            self.nodes[i].1 -= 1;
         }
         true
     }
-    pub fn xor_min(&self, mut x: u32) -> u32 {
+    // xors every value with x
+    pub fn xor_all(&mut self, x: T) {
+        let x = x.to_u64();
+        assert!(self.fits(x));
+        self.xor ^= x;
+    }
+    // min(y ^ x) over values y
+    pub fn xor_min(&self, x: T) -> T {
+        assert!(!self.is_empty());
+        let m = x.to_u64() ^ self.xor;
+        let mut i = 0;
+        let mut res = 0;
+        for j in (0..self.bits).rev() {
+            let mut f = m >> j & 1;
+            if self.child(i, f).is_none() {
+                f ^= 1;
+                res |= 1 << j;
+            }
+            i = self.child(i, f).unwrap();
+        }
+        T::from_u64(res)
+    }
+    // max(y ^ x) over values y
+    pub fn xor_max(&self, x: T) -> T {
+        assert!(!self.is_empty());
+        let m = x.to_u64() ^ self.xor;
         let mut i = 0;
-        for j in (0..Self::height()).rev() {
-            let mut f = (x >> j & 1) as usize;
-            if self.0[i][f] > !0 >> 1 {
+        let mut res = 0;
+        for j in (0..self.bits).rev() {
+            let mut f = !m >> j & 1;
+            if self.child(i, f).is_none() {
                 f ^= 1;
+            } else {
+                res |= 1 << j;
+            }
+            i = self.child(i, f).unwrap();
+        }
+        T::from_u64(res)
+    }
+    // the k-th (0-indexed) smallest of y ^ xor_mask over values y
+    pub fn kth(&self, k: usize, xor_mask: T) -> T {
+        assert!(k < self.len());
+        let m = xor_mask.to_u64() ^ self.xor;
+        let mut k = k;
+        let mut i = 0;
+        let mut res = 0;
+        for j in (0..self.bits).rev() {
+            let f = m >> j & 1;
+            let c = self.cnt(i, f);
+            if k < c {
+                i = self.child(i, f).unwrap();
+            } else {
+                k -= c;
+                res |= 1 << j;
+                i = self.child(i, f ^ 1).unwrap();
+            }
+        }
+        T::from_u64(res)
+    }
+    // the number of values less than x
+    pub fn count_less(&self, x: T) -> usize {
+        let x = x.to_u64();
+        if !self.fits(x) {
+            return self.len();
+        }
+        let mut i = 0;
+        let mut res = 0;
+        for j in (0..self.bits).rev() {
+            let f = self.xor >> j & 1;
+            if x >> j & 1 == 1 {
+                res += self.cnt(i, f);
+                match self.child(i, f ^ 1) {
+                    Some(c) => i = c,
+                    None => return res,
+                }
+            } else {
+                match self.child(i, f) {
+                    Some(c) => i = c,
+                    None => return res,
+                }
             }
-            x ^= (f as u32) << j;
-            i = self.0[i][f];
         }
-        x
+        res
+    }
+}
+impl<T: Unsigned> Default for BinaryTrie<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::*;
+
+    #[test]
+    fn binary_trie_random() {
+        let mut rand = Pcg::seed_from_u64(3501);
+        for &bits in &[5, 64] {
+            let mask = if bits == 64 { !0 } else { (1u64 << bits) - 1 };
+            let mut trie = BinaryTrie::<u64>::with_bits(bits);
+            // stored after xoring
+            let mut naive: Vec<u64> = vec![];
+            for _ in 0..3000 {
+                let x = if bits == 64 && rand.next_u32() & 1 == 0 {
+                    rand.next_u64()
+                } else {
+                    rand.next_u64() & mask & 31
+                };
+                match rand.next_u32() % 6 {
+                    0 | 1 => {
+                        assert_eq!(trie.add(x), !naive.contains(&x));
+                        naive.push(x);
+                    }
+                    2 => {
+                        let p = naive.iter().position(|&y| y == x);
+                        assert_eq!(trie.remove(x), p.is_some());
+                        if let Some(p) = p {
+                            naive.swap_remove(p);
+                        }
+                    }
+                    3 => {
+                        trie.xor_all(x);
+                        for y in &mut naive {
+                            *y ^= x;
+                        }
+                    }
+                    _ if !naive.is_empty() => {
+                        assert_eq!(trie.xor_min(x), naive.iter().map(|y| y ^ x).min().unwrap());
+                        assert_eq!(trie.xor_max(x), naive.iter().map(|y| y ^ x).max().unwrap());
+                        let mut b: Vec<_> = naive.iter().map(|y| y ^ x).collect();
+                        b.sort();
+                        let k = rand.next_u32() as usize % b.len();
+                        assert_eq!(trie.kth(k, x), b[k]);
+                    }
+                    _ => {}
+                }
+                assert_eq!(trie.len(), naive.len());
+                assert_eq!(trie.count(x), naive.iter().filter(|&&y| y == x).count());
+                assert_eq!(trie.count_less(x), naive.iter().filter(|&&y| y < x).count());
+            }
+        }
+    }
+
+    #[test]
+    fn binary_trie_u8() {
+        let mut trie = BinaryTrie::<u8>::new();
+        for x in [3, 200, 200, 17] {
+            trie.add(x);
+        }
+        assert_eq!(trie.xor_max(0), 200);
+        assert_eq!(trie.xor_min(255), 200 ^ 255);
+        trie.xor_all(255);
+        assert_eq!(trie.kth(0, 0), 55);
+        assert_eq!(trie.kth(1, 0), 55);
+        assert_eq!(trie.count_less(238), 2);
+        assert_eq!(trie.count(252), 1);
     }
 }
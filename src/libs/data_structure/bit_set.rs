@@ -1,35 +1,98 @@
-use std::ops::{Bound, RangeBounds};
-pub struct BitSet(Vec<u64>);
+use std::ops::{
+    BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Bound, Not, RangeBounds, Shl,
+    ShlAssign, Shr, ShrAssign,
+};
+// bits at len and beyond are always zero
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BitSet {
+    bits: Vec<u64>,
+    len: usize,
+}
 impl BitSet {
     pub fn new(n: usize, b: bool) -> Self {
         let x = if b { !0 } else { 0 };
-        Self(vec![x; n / 64 + 1])
+        let mut res = Self {
+            bits: vec![x; (n + 63) / 64],
+            len: n,
+        };
+        res.trim();
+        res
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    fn trim(&mut self) {
+        if self.len & 63 > 0 {
+            *self.bits.last_mut().unwrap() &= !0 >> (64 - self.len % 64);
+        }
     }
     pub fn get(&self, i: usize) -> bool {
-        self.0[i / 64] >> i % 64 & 1 == 1
+        assert!(i < self.len);
+        self.bits[i / 64] >> i % 64 & 1 == 1
     }
     pub fn set(&mut self, i: usize, b: bool) {
+        assert!(i < self.len);
         if b {
-            self.0[i / 64] |= 1 << i % 64;
+            self.bits[i / 64] |= 1 << i % 64;
         } else {
-            self.0[i / 64] &= !(1 << i % 64);
+            self.bits[i / 64] &= !(1 << i % 64);
         }
     }
     pub fn flip<R: RangeBounds<usize>>(&mut self, range: R) {
-        let (l, r) = Self::conv_range(range);
-        assert!(l <= r);
+        let (l, r) = self.conv_range(range);
         self.modify_range(l, r, |v, x| *v ^= x, |v| *v = !*v);
     }
     pub fn fill<R: RangeBounds<usize>>(&mut self, range: R, b: bool) {
-        let (l, r) = Self::conv_range(range);
-        assert!(l <= r);
+        let (l, r) = self.conv_range(range);
         if b {
             self.modify_range(l, r, |v, x| *v |= x, |v| *v = !0);
         } else {
             self.modify_range(l, r, |v, x| *v &= !x, |v| *v = 0);
         }
     }
-    fn conv_range<R: RangeBounds<usize>>(range: R) -> (usize, usize) {
+    pub fn count_ones<R: RangeBounds<usize>>(&self, range: R) -> usize {
+        let (l, r) = self.conv_range(range);
+        if l == r {
+            return 0;
+        }
+        let (ldiv, rdiv, lmask, rmask) = Self::masks(l, r);
+        let res = if ldiv == rdiv {
+            (self.bits[ldiv] & lmask & rmask).count_ones()
+        } else {
+            (self.bits[ldiv] & lmask).count_ones()
+                + self.bits[ldiv + 1..rdiv]
+                    .iter()
+                    .map(|v| v.count_ones())
+                    .sum::<u32>()
+                + (self.bits[rdiv] & rmask).count_ones()
+        };
+        res as usize
+    }
+    // the smallest set position not less than i
+    pub fn find_next(&self, i: usize) -> Option<usize> {
+        if i >= self.len {
+            return None;
+        }
+        let mut w = i / 64;
+        let mut x = self.bits[w] & !0 << i % 64;
+        while x == 0 {
+            w += 1;
+            x = *self.bits.get(w)?;
+        }
+        Some(64 * w + x.trailing_zeros() as usize)
+    }
+    // set positions in increasing order
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.bits.iter().enumerate().flat_map(|(i, &w)| {
+            std::iter::successors(Some(w), |&w| Some(w & w.wrapping_sub(1)))
+                .take_while(|&w| w != 0)
+                .map(move |w| 64 * i + w.trailing_zeros() as usize)
+        })
+    }
+    fn conv_range<R: RangeBounds<usize>>(&self, range: R) -> (usize, usize) {
         let l = match range.start_bound() {
             Bound::Included(i) => *i,
             Bound::Excluded(i) => i + 1,
@@ -38,10 +101,21 @@ impl BitSet {
         let r = match range.end_bound() {
             Bound::Included(i) => i + 1,
             Bound::Excluded(i) => *i,
-            Bound::Unbounded => !0,
+            Bound::Unbounded => self.len,
         };
+        assert!(l <= r);
+        assert!(r <= self.len);
         (l, r)
     }
+    // the words holding l and r - 1 and the masks of [l, r) in them
+    fn masks(l: usize, r: usize) -> (usize, usize, u64, u64) {
+        (
+            l / 64,
+            (r - 1) / 64,
+            !0 << l % 64,
+            !0 >> (63 - (r - 1) % 64),
+        )
+    }
     fn modify_range<F: FnMut(&mut u64, u64), G: FnMut(&mut u64)>(
         &mut self,
         l: usize,
@@ -49,34 +123,195 @@ impl BitSet {
         mut bound: F,
         mut inter: G,
     ) {
-        let ldiv = l / 64;
-        let lrem = l % 64;
-        let rdiv = r / 64;
-        let rrem = r % 64;
-        assert!(ldiv < self.0.len());
+        if l == r {
+            return;
+        }
+        let (ldiv, rdiv, lmask, rmask) = Self::masks(l, r);
         if ldiv == rdiv {
-            bound(&mut self.0[ldiv], !0 << lrem & !0 >> 64 - rrem);
+            bound(&mut self.bits[ldiv], lmask & rmask);
         } else {
-            bound(&mut self.0[ldiv], !0 << lrem);
-            let end = rdiv.min(self.0.len());
-            for v in &mut self.0[ldiv + 1..end] {
+            bound(&mut self.bits[ldiv], lmask);
+            for v in &mut self.bits[ldiv + 1..rdiv] {
                 inter(v);
             }
-            if rdiv < self.0.len() && rrem != 0 {
-                if let Some(v) = self.0.get_mut(rdiv) {
-                    bound(v, !0 >> 64 - rrem);
-                }
+            bound(&mut self.bits[rdiv], rmask);
+        }
+    }
+}
+// moves bit i to i + s, dropping the bits shifted past len
+impl ShlAssign<usize> for BitSet {
+    fn shl_assign(&mut self, s: usize) {
+        let (ws, bs) = (s / 64, s % 64);
+        let n = self.bits.len();
+        for i in (0..n).rev() {
+            let mut x = if i >= ws { self.bits[i - ws] << bs } else { 0 };
+            if bs > 0 && i > ws {
+                x |= self.bits[i - ws - 1] >> (64 - bs);
             }
+            self.bits[i] = x;
         }
+        self.trim();
     }
 }
+// moves bit i to i - s
+impl ShrAssign<usize> for BitSet {
+    fn shr_assign(&mut self, s: usize) {
+        let (ws, bs) = (s / 64, s % 64);
+        let n = self.bits.len();
+        for i in 0..n {
+            let mut x = if i + ws < n {
+                self.bits[i + ws] >> bs
+            } else {
+                0
+            };
+            if bs > 0 && i + ws + 1 < n {
+                x |= self.bits[i + ws + 1] << (64 - bs);
+            }
+            self.bits[i] = x;
+        }
+    }
+}
+impl Shl<usize> for &BitSet {
+    type Output = BitSet;
+    fn shl(self, s: usize) -> BitSet {
+        let mut res = self.clone();
+        res <<= s;
+        res
+    }
+}
+impl Shr<usize> for &BitSet {
+    type Output = BitSet;
+    fn shr(self, s: usize) -> BitSet {
+        let mut res = self.clone();
+        res >>= s;
+        res
+    }
+}
+impl Not for &BitSet {
+    type Output = BitSet;
+    fn not(self) -> BitSet {
+        let mut res = self.clone();
+        res.flip(..);
+        res
+    }
+}
+macro_rules! bit_op_impl {
+    ($($Op:ident $op:ident $OpAssign:ident $op_assign:ident,)*) => {$(
+        impl $OpAssign<&BitSet> for BitSet {
+            fn $op_assign(&mut self, other: &BitSet) {
+                assert_eq!(self.len, other.len);
+                for (x, y) in self.bits.iter_mut().zip(&other.bits) {
+                    x.$op_assign(*y);
+                }
+            }
+        }
+        impl $Op for &BitSet {
+            type Output = BitSet;
+            fn $op(self, other: &BitSet) -> BitSet {
+                let mut res = self.clone();
+                res.$op_assign(other);
+                res
+            }
+        }
+    )*};
+}
+bit_op_impl! {
+    BitAnd bitand BitAndAssign bitand_assign,
+    BitOr bitor BitOrAssign bitor_assign,
+    BitXor bitxor BitXorAssign bitxor_assign,
+}
 
 #[cfg(test)]
 mod tests {
-    // use super::*;
+    use super::*;
+    use crate::random::*;
+
+    fn from_bools(a: &[bool]) -> BitSet {
+        let mut res = BitSet::new(a.len(), false);
+        for (i, &b) in a.iter().enumerate() {
+            res.set(i, b);
+        }
+        res
+    }
+    fn check(s: &BitSet, a: &[bool]) {
+        assert_eq!(s.len(), a.len());
+        assert!((0..a.len()).all(|i| s.get(i) == a[i]));
+        assert!(s.ones().eq((0..a.len()).filter(|&i| a[i])));
+    }
 
     #[test]
     fn bit_set() {
-        // todo
+        let mut rand = Pcg::seed_from_u64(3601);
+        for &n in &[0, 1, 63, 64, 65, 200] {
+            let mut a = vec![false; n];
+            let mut s = BitSet::new(n, false);
+            for _ in 0..300 {
+                let l = rand.next_u32() as usize % (n + 1);
+                let r = l + rand.next_u32() as usize % (n + 1 - l);
+                let i = rand.next_u32() as usize % (n + 1);
+                match rand.next_u32() % 8 {
+                    0 if i < n => {
+                        let b = rand.next_u32() & 1 == 1;
+                        s.set(i, b);
+                        a[i] = b;
+                    }
+                    1 => {
+                        s.flip(l..r);
+                        a[l..r].iter_mut().for_each(|b| *b = !*b);
+                    }
+                    2 => {
+                        let b = rand.next_u32() & 1 == 1;
+                        s.fill(l..r, b);
+                        a[l..r].iter_mut().for_each(|x| *x = b);
+                    }
+                    3 => {
+                        s = &s << i;
+                        a = (0..n).map(|j| j >= i && a[j - i]).collect();
+                    }
+                    4 => {
+                        s >>= i;
+                        a = (0..n).map(|j| j + i < n && a[j + i]).collect();
+                    }
+                    5 => {
+                        let b: Vec<bool> = (0..n).map(|_| rand.next_u32() & 1 == 1).collect();
+                        let t = from_bools(&b);
+                        let f: fn(bool, bool) -> bool = match rand.next_u32() % 3 {
+                            0 => {
+                                s = &s & &t;
+                                |x, y| x & y
+                            }
+                            1 => {
+                                s |= &t;
+                                |x, y| x | y
+                            }
+                            _ => {
+                                s = &s ^ &t;
+                                |x, y| x ^ y
+                            }
+                        };
+                        a = a.iter().zip(&b).map(|(&x, &y)| f(x, y)).collect();
+                    }
+                    6 => {
+                        s = !&s;
+                        a.iter_mut().for_each(|b| *b = !*b);
+                    }
+                    _ => {}
+                }
+                check(&s, &a);
+                assert_eq!(s.count_ones(l..r), a[l..r].iter().filter(|&&b| b).count());
+                assert_eq!(s.find_next(i), (i..n).find(|&j| a[j]));
+            }
+        }
+    }
+
+    #[test]
+    fn bit_set_subset_sum() {
+        let w = [3, 5, 11];
+        let mut dp = BitSet::new(20, false);
+        dp.set(0, true);
+        for &x in &w {
+            dp |= &(&dp << x);
+        }
+        assert_eq!(dp.ones().collect::<Vec<_>>(), [0, 3, 5, 8, 11, 14, 16, 19]);
     }
 }
@@ -0,0 +1,135 @@
+use std::collections::VecDeque;
+/// Lines must be added in non-increasing order of slope for min, non-decreasing for max.
+/// Hull maintenance multiplies differences in `i128`, so it is exact while `|a|, |b| < 2^62`.
+pub struct MonotoneCht {
+    lines: VecDeque<(i128, i128)>,
+    max: bool,
+}
+impl MonotoneCht {
+    pub fn new_min() -> Self {
+        Self::new(false)
+    }
+    pub fn new_max() -> Self {
+        Self::new(true)
+    }
+    fn new(max: bool) -> Self {
+        Self {
+            lines: VecDeque::new(),
+            max,
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+    pub fn add_line(&mut self, a: i64, b: i64) {
+        let (a, b) = if self.max {
+            (-(a as i128), -(b as i128))
+        } else {
+            (a as i128, b as i128)
+        };
+        if let Some(&(la, lb)) = self.lines.back() {
+            assert!(la >= a, "slopes must be monotone");
+            if la == a {
+                if lb <= b {
+                    return;
+                }
+                self.lines.pop_back();
+            }
+        }
+        while self.lines.len() >= 2 {
+            let (a1, b1) = self.lines[self.lines.len() - 2];
+            let (a2, b2) = self.lines[self.lines.len() - 1];
+            if (b - b1) * (a1 - a2) <= (b2 - b1) * (a1 - a) {
+                self.lines.pop_back();
+            } else {
+                break;
+            }
+        }
+        self.lines.push_back((a, b));
+    }
+    fn eval(&self, i: usize, x: i64) -> i128 {
+        let (a, b) = self.lines[i];
+        a * x as i128 + b
+    }
+    fn output(&self, y: i128) -> i128 {
+        if self.max {
+            -y
+        } else {
+            y
+        }
+    }
+    pub fn query(&self, x: i64) -> Option<i128> {
+        if self.lines.is_empty() {
+            return None;
+        }
+        let mut l = 0;
+        let mut r = self.lines.len() - 1;
+        while l < r {
+            let h = (l + r) / 2;
+            if self.eval(h, x) >= self.eval(h + 1, x) {
+                l = h + 1;
+            } else {
+                r = h;
+            }
+        }
+        Some(self.output(self.eval(l, x)))
+    }
+    // x must be non-decreasing over calls
+    pub fn query_monotone(&mut self, x: i64) -> Option<i128> {
+        while self.lines.len() >= 2 && self.eval(0, x) >= self.eval(1, x) {
+            self.lines.pop_front();
+        }
+        if self.lines.is_empty() {
+            None
+        } else {
+            Some(self.output(self.eval(0, x)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::*;
+
+    #[test]
+    fn monotone_cht() {
+        let mut rand = Pcg::seed_from_u64(2605);
+        for &max in &[false, true] {
+            for _ in 0..20 {
+                let mut lines: Vec<_> = (0..30)
+                    .map(|_| {
+                        let a = (rand.next_u32() % 201) as i64 - 100;
+                        let b = (rand.next_u32() % 20001) as i64 - 10000;
+                        (a, b)
+                    })
+                    .collect();
+                lines.sort_by_key(|&(a, _)| if max { a } else { -a });
+                let mut cht = if max {
+                    MonotoneCht::new_max()
+                } else {
+                    MonotoneCht::new_min()
+                };
+                let mut mono = if max {
+                    MonotoneCht::new_max()
+                } else {
+                    MonotoneCht::new_min()
+                };
+                assert_eq!(cht.query(0), None);
+                for &(a, b) in &lines {
+                    cht.add_line(a, b);
+                    mono.add_line(a, b);
+                }
+                for x in -200..=200 {
+                    let ys = lines.iter().map(|&(a, b)| (a * x + b) as i128);
+                    let naive = if max { ys.max() } else { ys.min() };
+                    assert_eq!(cht.query(x), naive);
+                    assert_eq!(mono.query_monotone(x), naive);
+                }
+            }
+        }
+    }
+}
@@ -1,3 +1,18 @@
+fn groups_by<F: FnMut(usize) -> usize>(n: usize, mut root: F) -> Vec<Vec<usize>> {
+    let mut id = vec![!0; n];
+    let mut res = Vec::new();
+    for u in 0..n {
+        let r = root(u);
+        if id[r] == !0 {
+            id[r] = res.len();
+            res.push(Vec::new());
+        }
+        res[id[r]].push(u);
+    }
+    res
+}
+
+#[derive(Clone, Debug)]
 pub struct Dsu(Vec<isize>);
 impl Dsu {
     pub fn new(n: usize) -> Self {
@@ -15,6 +30,9 @@ impl Dsu {
     pub fn unite(&mut self, u: usize, v: usize) -> (usize, usize) {
         let ru = self.root(u);
         let rv = self.root(v);
+        self.link(ru, rv)
+    }
+    fn link(&mut self, ru: usize, rv: usize) -> (usize, usize) {
         if ru == rv {
             return (ru, ru);
         }
@@ -33,12 +51,195 @@ impl Dsu {
     pub fn size(&self, u: usize) -> usize {
         -self.0[self.root(u)] as usize
     }
+    // components ordered by their smallest vertex
+    pub fn groups(&self) -> Vec<Vec<usize>> {
+        groups_by(self.0.len(), |u| self.root(u))
+    }
 }
 
-use std::mem::ManuallyDrop;
+#[derive(Clone, Debug)]
+pub struct FastDsu(Vec<isize>);
+impl FastDsu {
+    pub fn new(n: usize) -> Self {
+        Self(vec![-1; n])
+    }
+    pub fn root(&mut self, u: usize) -> usize {
+        let mut r = u;
+        while self.0[r] >= 0 {
+            r = self.0[r] as usize;
+        }
+        let mut u = u;
+        while self.0[u] >= 0 {
+            let p = self.0[u] as usize;
+            self.0[u] = r as isize;
+            u = p;
+        }
+        r
+    }
+    pub fn is_root(&self, u: usize) -> bool {
+        self.0[u] < 0
+    }
+    pub fn unite(&mut self, u: usize, v: usize) -> (usize, usize) {
+        let ru = self.root(u);
+        let rv = self.root(v);
+        if ru == rv {
+            return (ru, ru);
+        }
+        let (r, c) = if -self.0[ru] >= -self.0[rv] {
+            (ru, rv)
+        } else {
+            (rv, ru)
+        };
+        self.0[r] += self.0[c];
+        self.0[c] = r as isize;
+        (r, c)
+    }
+    pub fn is_same(&mut self, u: usize, v: usize) -> bool {
+        self.root(u) == self.root(v)
+    }
+    pub fn size(&mut self, u: usize) -> usize {
+        let r = self.root(u);
+        -self.0[r] as usize
+    }
+    pub fn groups(&mut self) -> Vec<Vec<usize>> {
+        groups_by(self.0.len(), |u| self.root(u))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RollbackDsu {
+    inner: Dsu,
+    history: Vec<(usize, isize)>,
+}
+impl RollbackDsu {
+    pub fn new(n: usize) -> Self {
+        Self {
+            inner: Dsu::new(n),
+            history: Vec::new(),
+        }
+    }
+    pub fn root(&self, u: usize) -> usize {
+        self.inner.root(u)
+    }
+    pub fn is_root(&self, u: usize) -> bool {
+        self.inner.is_root(u)
+    }
+    pub fn unite(&mut self, u: usize, v: usize) -> (usize, usize) {
+        let ru = self.inner.root(u);
+        let rv = self.inner.root(v);
+        if ru != rv {
+            self.history.push((ru, self.inner.0[ru]));
+            self.history.push((rv, self.inner.0[rv]));
+        }
+        self.inner.link(ru, rv)
+    }
+    pub fn is_same(&self, u: usize, v: usize) -> bool {
+        self.inner.is_same(u, v)
+    }
+    pub fn size(&self, u: usize) -> usize {
+        self.inner.size(u)
+    }
+    pub fn groups(&self) -> Vec<Vec<usize>> {
+        self.inner.groups()
+    }
+    pub fn snapshot(&self) -> usize {
+        self.history.len()
+    }
+    // undoes every unite after the snapshot
+    pub fn rollback(&mut self, snapshot: usize) {
+        assert!(snapshot <= self.history.len());
+        for (i, x) in self.history.drain(snapshot..).rev() {
+            self.inner.0[i] = x;
+        }
+    }
+}
+
+// diff(u, v) is the potential of v minus that of u
+#[derive(Clone, Debug)]
+pub struct WeightedDsu<T> {
+    par: Vec<isize>,
+    weight: Vec<T>,
+}
+impl<T> WeightedDsu<T>
+where
+    T: Copy + Default + PartialEq + std::ops::Add<Output = T> + std::ops::Sub<Output = T>,
+{
+    pub fn new(n: usize) -> Self {
+        Self {
+            par: vec![-1; n],
+            weight: vec![T::default(); n],
+        }
+    }
+    // returns the root and the potential of u relative to it
+    fn find(&mut self, u: usize) -> (usize, T) {
+        let mut path = Vec::new();
+        let mut r = u;
+        while self.par[r] >= 0 {
+            path.push(r);
+            r = self.par[r] as usize;
+        }
+        let mut w = T::default();
+        for &v in path.iter().rev() {
+            w = w + self.weight[v];
+            self.weight[v] = w;
+            self.par[v] = r as isize;
+        }
+        (
+            r,
+            if path.is_empty() {
+                T::default()
+            } else {
+                self.weight[u]
+            },
+        )
+    }
+    pub fn root(&mut self, u: usize) -> usize {
+        self.find(u).0
+    }
+    // imposes diff(u, v) == d; returns false if it contradicts the known differences
+    pub fn unite(&mut self, u: usize, v: usize, d: T) -> bool {
+        let (ru, wu) = self.find(u);
+        let (rv, wv) = self.find(v);
+        if ru == rv {
+            return wv - wu == d;
+        }
+        if -self.par[ru] >= -self.par[rv] {
+            self.par[ru] += self.par[rv];
+            self.par[rv] = ru as isize;
+            self.weight[rv] = d + wu - wv;
+        } else {
+            self.par[rv] += self.par[ru];
+            self.par[ru] = rv as isize;
+            self.weight[ru] = wv - wu - d;
+        }
+        true
+    }
+    pub fn diff(&mut self, u: usize, v: usize) -> Option<T> {
+        let (ru, wu) = self.find(u);
+        let (rv, wv) = self.find(v);
+        if ru == rv {
+            Some(wv - wu)
+        } else {
+            None
+        }
+    }
+    pub fn is_same(&mut self, u: usize, v: usize) -> bool {
+        self.root(u) == self.root(v)
+    }
+    pub fn size(&mut self, u: usize) -> usize {
+        let r = self.root(u);
+        -self.par[r] as usize
+    }
+    pub fn groups(&mut self) -> Vec<Vec<usize>> {
+        groups_by(self.par.len(), |u| self.root(u))
+    }
+}
+
+use std::{fmt, mem::MaybeUninit};
+// only the data of roots is initialized
 pub struct DsuWithData<T> {
     inner: Dsu,
-    data: Vec<ManuallyDrop<T>>,
+    data: Vec<MaybeUninit<T>>,
 }
 impl<T> DsuWithData<T> {
     pub fn unite<F>(&mut self, u: usize, v: usize, mut merge: F) -> (usize, usize)
@@ -48,8 +249,8 @@ impl<T> DsuWithData<T> {
         let (r, c) = self.inner.unite(u, v);
         if r != c {
             unsafe {
-                let dc = ManuallyDrop::take(&mut self.data[c]);
-                merge(&mut self.data[r], dc);
+                let dc = self.data[c].as_ptr().read();
+                merge(&mut *self.data[r].as_mut_ptr(), dc);
             }
         }
         (r, c)
@@ -66,10 +267,24 @@ impl<T> DsuWithData<T> {
     pub fn size(&self, u: usize) -> usize {
         self.inner.size(u)
     }
+    pub fn groups(&self) -> Vec<Vec<usize>> {
+        self.inner.groups()
+    }
+    pub fn into_groups(mut self) -> Vec<(Vec<usize>, T)> {
+        let groups = self.inner.groups();
+        let data = std::mem::take(&mut self.data);
+        groups
+            .into_iter()
+            .map(|g| {
+                let r = self.inner.root(g[0]);
+                (g, unsafe { data[r].as_ptr().read() })
+            })
+            .collect()
+    }
 }
 impl<T> std::iter::FromIterator<T> for DsuWithData<T> {
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
-        let data: Vec<_> = iter.into_iter().map(ManuallyDrop::new).collect();
+        let data: Vec<_> = iter.into_iter().map(MaybeUninit::new).collect();
         Self {
             inner: Dsu::new(data.len()),
             data,
@@ -79,14 +294,44 @@ impl<T> std::iter::FromIterator<T> for DsuWithData<T> {
 impl<T> std::ops::Index<usize> for DsuWithData<T> {
     type Output = T;
     fn index(&self, u: usize) -> &T {
-        &self.data[self.root(u)]
+        unsafe { &*self.data[self.root(u)].as_ptr() }
+    }
+}
+impl<T: Clone> Clone for DsuWithData<T> {
+    fn clone(&self) -> Self {
+        let data = (0..self.data.len())
+            .map(|u| {
+                if self.is_root(u) {
+                    MaybeUninit::new(self[u].clone())
+                } else {
+                    MaybeUninit::uninit()
+                }
+            })
+            .collect();
+        Self {
+            inner: self.inner.clone(),
+            data,
+        }
+    }
+}
+impl<T: fmt::Debug> fmt::Debug for DsuWithData<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(
+                (0..self.data.len())
+                    .filter(|&u| self.is_root(u))
+                    .map(|u| (u, &self[u])),
+            )
+            .finish()
     }
 }
 impl<T> Drop for DsuWithData<T> {
     fn drop(&mut self) {
-        for (p, d) in self.inner.0.iter().zip(self.data.drain(..)) {
+        for (p, d) in self.inner.0.iter().zip(self.data.iter_mut()) {
             if *p < 0 {
-                ManuallyDrop::into_inner(d);
+                unsafe {
+                    d.as_mut_ptr().drop_in_place();
+                }
             }
         }
     }
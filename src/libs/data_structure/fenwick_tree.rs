@@ -1,3 +1,20 @@
+use std::ops::{Add, Bound, Mul, RangeBounds, Sub};
+fn conv_range<R: RangeBounds<usize>>(range: R, n: usize) -> (usize, usize) {
+    let l = match range.start_bound() {
+        Bound::Included(i) => *i,
+        Bound::Excluded(i) => i + 1,
+        Bound::Unbounded => 0,
+    };
+    let r = match range.end_bound() {
+        Bound::Included(i) => i + 1,
+        Bound::Excluded(i) => *i,
+        Bound::Unbounded => n,
+    };
+    assert!(l <= r);
+    assert!(r <= n);
+    (l, r)
+}
+
 #[derive(Clone)]
 pub struct FenwickTree<T, F, Z> {
     a: Vec<T>,
@@ -15,6 +32,9 @@ impl<T, F: Fn(&T, &T) -> T, Z: Fn() -> T> FenwickTree<T, F, Z> {
     pub fn len(&self) -> usize {
         self.a.len() - 1
     }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
     pub fn add(&mut self, mut i: usize, x: T) {
         i += 1;
         while i < self.a.len() {
@@ -34,9 +54,150 @@ impl<T, F: Fn(&T, &T) -> T, Z: Fn() -> T> FenwickTree<T, F, Z> {
     pub fn sum_all(&self) -> T {
         self.sum(self.len())
     }
+    // sub(x, y) must be the inverse of f, i.e. sub(f(x, y), y) == x
+    pub fn range_sum<R: RangeBounds<usize>, G: Fn(&T, &T) -> T>(&self, range: R, sub: G) -> T {
+        let (l, r) = conv_range(range, self.len());
+        sub(&self.sum(r), &self.sum(l))
+    }
+    // the smallest i such that sum(i + 1) >= w, or len() if there is none;
+    // prefix sums must be non-decreasing
+    pub fn lower_bound(&self, w: &T) -> usize
+    where
+        T: Ord,
+    {
+        let mut i = 0;
+        let mut s = (self.z)();
+        let mut k = self.a.len().next_power_of_two() / 2;
+        while k > 0 {
+            if i + k < self.a.len() {
+                let t = (self.f)(&s, &self.a[i + k]);
+                if &t < w {
+                    i += k;
+                    s = t;
+                }
+            }
+            k /= 2;
+        }
+        i
+    }
     pub fn reset(&mut self) {
         for a in &mut self.a {
             *a = (self.z)();
         }
     }
 }
+
+#[derive(Clone)]
+pub struct RangeAddFenwickTree<T>(Vec<(T, T)>);
+impl<T> RangeAddFenwickTree<T>
+where
+    T: Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + From<u32>,
+{
+    pub fn new(n: usize) -> Self {
+        Self(vec![(T::default(), T::default()); n + 1])
+    }
+    pub fn len(&self) -> usize {
+        self.0.len() - 1
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    fn modify(&mut self, mut i: usize, x: T, neg: bool) {
+        let xi = x * T::from(i as u32);
+        i += 1;
+        while i < self.0.len() {
+            let (s0, s1) = &mut self.0[i];
+            if neg {
+                *s0 = *s0 - xi;
+                *s1 = *s1 - x;
+            } else {
+                *s0 = *s0 + xi;
+                *s1 = *s1 + x;
+            }
+            i += i & (!i + 1);
+        }
+    }
+    // [l, r)
+    pub fn add(&mut self, l: usize, r: usize, x: T) {
+        assert!(l <= r);
+        assert!(r <= self.len());
+        self.modify(l, x, false);
+        self.modify(r, x, true);
+    }
+    // [0, i)
+    pub fn sum(&self, i: usize) -> T {
+        let mut s0 = T::default();
+        let mut s1 = T::default();
+        let mut j = i;
+        while j > 0 {
+            s0 = s0 + self.0[j].0;
+            s1 = s1 + self.0[j].1;
+            j -= j & (!j + 1);
+        }
+        s1 * T::from(i as u32) - s0
+    }
+    pub fn range_sum<R: RangeBounds<usize>>(&self, range: R) -> T {
+        let (l, r) = conv_range(range, self.len());
+        self.sum(r) - self.sum(l)
+    }
+}
+
+#[derive(Clone)]
+pub struct FenwickTree2d<T, F, Z> {
+    a: Vec<T>,
+    w: usize,
+    f: F,
+    z: Z,
+}
+impl<T, F: Fn(&T, &T) -> T, Z: Fn() -> T> FenwickTree2d<T, F, Z> {
+    pub fn new(h: usize, w: usize, z: Z, f: F) -> Self {
+        Self {
+            a: (0..(h + 1) * (w + 1)).map(|_| z()).collect(),
+            w: w + 1,
+            f,
+            z,
+        }
+    }
+    pub fn dim(&self) -> (usize, usize) {
+        (self.a.len() / self.w - 1, self.w - 1)
+    }
+    pub fn add(&mut self, i: usize, j: usize, x: T) {
+        let (h, w) = self.dim();
+        let mut i = i + 1;
+        while i <= h {
+            let mut j = j + 1;
+            while j <= w {
+                let k = i * self.w + j;
+                self.a[k] = (self.f)(&self.a[k], &x);
+                j += j & (!j + 1);
+            }
+            i += i & (!i + 1);
+        }
+    }
+    // [0, i) x [0, j)
+    pub fn sum(&self, mut i: usize, j: usize) -> T {
+        let mut s = (self.z)();
+        while i > 0 {
+            let mut j = j;
+            while j > 0 {
+                s = (self.f)(&self.a[i * self.w + j], &s);
+                j -= j & (!j + 1);
+            }
+            i -= i & (!i + 1);
+        }
+        s
+    }
+    pub fn rect_sum<R1, R2, G>(&self, rows: R1, cols: R2, sub: G) -> T
+    where
+        R1: RangeBounds<usize>,
+        R2: RangeBounds<usize>,
+        G: Fn(&T, &T) -> T,
+    {
+        let (h, w) = self.dim();
+        let (il, ir) = conv_range(rows, h);
+        let (jl, jr) = conv_range(cols, w);
+        let s = sub(&self.sum(ir, jr), &self.sum(il, jr));
+        let s = sub(&s, &self.sum(ir, jl));
+        (self.f)(&s, &self.sum(il, jl))
+    }
+}
@@ -0,0 +1,454 @@
+use std::collections::BinaryHeap;
+
+type Tree<T> = Option<Box<Node<T>>>;
+struct Node<T> {
+    val: T,
+    // length of the right spine
+    rank: usize,
+    left: Tree<T>,
+    right: Tree<T>,
+}
+fn rank<T>(t: &Tree<T>) -> usize {
+    t.as_ref().map(|n| n.rank).unwrap_or(0)
+}
+// recurses only along right spines, which have O(log n) length
+fn meld<T: Ord>(a: Tree<T>, b: Tree<T>) -> Tree<T> {
+    match (a, b) {
+        (None, b) => b,
+        (a, None) => a,
+        (Some(mut a), Some(mut b)) => {
+            if a.val < b.val {
+                std::mem::swap(&mut a, &mut b);
+            }
+            a.right = meld(a.right.take(), Some(b));
+            if rank(&a.left) < rank(&a.right) {
+                std::mem::swap(&mut a.left, &mut a.right);
+            }
+            a.rank = rank(&a.right) + 1;
+            Some(a)
+        }
+    }
+}
+
+// max-heap like `BinaryHeap`
+pub struct LeftistHeap<T> {
+    root: Tree<T>,
+    len: usize,
+}
+impl<T: Ord> LeftistHeap<T> {
+    pub fn new() -> Self {
+        Self { root: None, len: 0 }
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub fn peek(&self) -> Option<&T> {
+        self.root.as_ref().map(|n| &n.val)
+    }
+    pub fn push(&mut self, x: T) {
+        let node = Box::new(Node {
+            val: x,
+            rank: 1,
+            left: None,
+            right: None,
+        });
+        self.root = meld(self.root.take(), Some(node));
+        self.len += 1;
+    }
+    pub fn pop(&mut self) -> Option<T> {
+        let mut node = self.root.take()?;
+        self.root = meld(node.left.take(), node.right.take());
+        self.len -= 1;
+        Some(node.val)
+    }
+    // moves all the elements of other into self
+    pub fn meld(&mut self, other: Self) {
+        let mut other = other;
+        self.root = meld(self.root.take(), other.root.take());
+        self.len += other.len;
+    }
+}
+impl<T: Ord> Default for LeftistHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<T: Ord> std::iter::FromIterator<T> for LeftistHeap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut res = Self::new();
+        for x in iter {
+            res.push(x);
+        }
+        res
+    }
+}
+impl<T> Drop for LeftistHeap<T> {
+    // left spines can be long
+    fn drop(&mut self) {
+        let mut stack: Vec<_> = self.root.take().into_iter().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.left.take());
+            stack.extend(node.right.take());
+        }
+    }
+}
+
+// min-heap whose popped keys never decrease; push(key) requires key >= the last popped key
+pub struct RadixHeap<T> {
+    buckets: Vec<Vec<(u64, T)>>,
+    last: u64,
+    len: usize,
+}
+impl<T> RadixHeap<T> {
+    pub fn new() -> Self {
+        Self {
+            buckets: (0..65).map(|_| Vec::new()).collect(),
+            last: 0,
+            len: 0,
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    fn bucket(&self, key: u64) -> usize {
+        64 - (key ^ self.last).leading_zeros() as usize
+    }
+    pub fn push(&mut self, key: u64, x: T) {
+        assert!(key >= self.last);
+        let b = self.bucket(key);
+        self.buckets[b].push((key, x));
+        self.len += 1;
+    }
+    pub fn pop(&mut self) -> Option<(u64, T)> {
+        if self.len == 0 {
+            return None;
+        }
+        if self.buckets[0].is_empty() {
+            let i = (1..65).find(|&i| !self.buckets[i].is_empty()).unwrap();
+            let v = std::mem::take(&mut self.buckets[i]);
+            self.last = v.iter().map(|e| e.0).min().unwrap();
+            for (key, x) in v {
+                let b = self.bucket(key);
+                self.buckets[b].push((key, x));
+            }
+        }
+        self.len -= 1;
+        self.buckets[0].pop()
+    }
+}
+impl<T> Default for RadixHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// double-ended priority queue; a[2i] <= a[2i + 1] hold the min and the max of the subtree of node i
+pub struct IntervalHeap<T>(Vec<T>);
+impl<T: Ord> IntervalHeap<T> {
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    pub fn peek_min(&self) -> Option<&T> {
+        self.0.first()
+    }
+    pub fn peek_max(&self) -> Option<&T> {
+        self.0.get(1).or(self.0.first())
+    }
+    pub fn push(&mut self, x: T) {
+        self.0.push(x);
+        let a = &mut self.0;
+        let mut k = a.len() - 1;
+        if k & 1 == 1 && a[k - 1] > a[k] {
+            a.swap(k - 1, k);
+            k -= 1;
+        }
+        while k >= 2 {
+            let p = (k / 2 - 1) / 2 * 2;
+            if a[p] > a[k] {
+                a.swap(p, k);
+                k = p;
+            } else if a[p + 1] < a[k] {
+                a.swap(p + 1, k);
+                k = p + 1;
+            } else {
+                break;
+            }
+        }
+    }
+    pub fn pop_min(&mut self) -> Option<T> {
+        if self.0.len() <= 1 {
+            return self.0.pop();
+        }
+        let res = self.0.swap_remove(0);
+        let a = &mut self.0;
+        let n = a.len();
+        let mut k = 0;
+        loop {
+            let l = k / 2 * 4 + 2;
+            if l >= n {
+                break;
+            }
+            let c = if l + 2 < n && a[l + 2] < a[l] {
+                l + 2
+            } else {
+                l
+            };
+            if a[c] >= a[k] {
+                break;
+            }
+            a.swap(c, k);
+            k = c;
+            if k + 1 < n && a[k] > a[k + 1] {
+                a.swap(k, k + 1);
+            }
+        }
+        Some(res)
+    }
+    pub fn pop_max(&mut self) -> Option<T> {
+        if self.0.len() <= 2 {
+            return self.0.pop();
+        }
+        let res = self.0.swap_remove(1);
+        let a = &mut self.0;
+        let n = a.len();
+        let mut k = 1;
+        loop {
+            let l = k / 2 * 4 + 2;
+            if l >= n {
+                break;
+            }
+            // the maximum of a child is its only element if it has one
+            let mut c = if l + 1 < n { l + 1 } else { l };
+            if l + 2 < n {
+                let d = if l + 3 < n { l + 3 } else { l + 2 };
+                if a[d] > a[c] {
+                    c = d;
+                }
+            }
+            if a[c] <= a[k] {
+                break;
+            }
+            a.swap(c, k);
+            k = c;
+            if k & 1 == 1 && a[k - 1] > a[k] {
+                a.swap(k - 1, k);
+            }
+        }
+        Some(res)
+    }
+}
+impl<T: Ord> Default for IntervalHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<T: Ord> std::iter::FromIterator<T> for IntervalHeap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut res = Self::new();
+        for x in iter {
+            res.push(x);
+        }
+        res
+    }
+}
+
+// max-heap supporting removal of arbitrary elements that are present
+pub struct RemovableHeap<T> {
+    heap: BinaryHeap<T>,
+    removed: BinaryHeap<T>,
+}
+impl<T: Ord> RemovableHeap<T> {
+    pub fn new() -> Self {
+        Self {
+            heap: BinaryHeap::new(),
+            removed: BinaryHeap::new(),
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.heap.len() - self.removed.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn push(&mut self, x: T) {
+        self.heap.push(x);
+    }
+    // x must be in the heap
+    pub fn remove(&mut self, x: T) {
+        self.removed.push(x);
+    }
+    fn normalize(&mut self) {
+        while self.removed.peek().is_some() && self.heap.peek() == self.removed.peek() {
+            self.heap.pop();
+            self.removed.pop();
+        }
+    }
+    pub fn peek(&mut self) -> Option<&T> {
+        self.normalize();
+        self.heap.peek()
+    }
+    pub fn pop(&mut self) -> Option<T> {
+        self.normalize();
+        self.heap.pop()
+    }
+}
+impl<T: Ord> Default for RemovableHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::*;
+    use std::collections::BTreeMap;
+
+    fn insert(set: &mut BTreeMap<u32, usize>, x: u32) {
+        *set.entry(x).or_insert(0) += 1;
+    }
+    fn erase(set: &mut BTreeMap<u32, usize>, x: u32) {
+        let c = set.get_mut(&x).unwrap();
+        *c -= 1;
+        if *c == 0 {
+            set.remove(&x);
+        }
+    }
+
+    #[test]
+    fn leftist_heap_random() {
+        let mut rand = Pcg::seed_from_u64(3401);
+        let mut heaps: Vec<LeftistHeap<u32>> = (0..5).map(|_| LeftistHeap::new()).collect();
+        let mut naive: Vec<BinaryHeap<u32>> = (0..5).map(|_| BinaryHeap::new()).collect();
+        for _ in 0..3000 {
+            let i = rand.next_u32() as usize % 5;
+            match rand.next_u32() % 4 {
+                0 | 1 => {
+                    let x = rand.next_u32() % 100;
+                    heaps[i].push(x);
+                    naive[i].push(x);
+                }
+                2 => assert_eq!(heaps[i].pop(), naive[i].pop()),
+                _ => {
+                    let j = rand.next_u32() as usize % 5;
+                    if i != j {
+                        let h = std::mem::take(&mut heaps[j]);
+                        heaps[i].meld(h);
+                        let h = std::mem::take(&mut naive[j]);
+                        naive[i].extend(h);
+                    }
+                }
+            }
+            assert_eq!(heaps[i].peek(), naive[i].peek());
+            assert_eq!(heaps[i].len(), naive[i].len());
+        }
+        let long: LeftistHeap<u32> = (0..1_000_000).collect();
+        assert_eq!(long.peek(), Some(&999_999));
+    }
+
+    #[test]
+    fn radix_heap_random() {
+        let mut rand = Pcg::seed_from_u64(3402);
+        let mut heap = RadixHeap::new();
+        let mut naive = BTreeMap::new();
+        let mut last = 0;
+        for i in 0..3000 {
+            if rand.next_u32() % 3 < 2 {
+                let key = last + rand.next_u64() % 1000 * (1 << (rand.next_u32() % 40));
+                heap.push(key, i);
+                naive.insert((key, i), ());
+            } else {
+                let x = heap.pop();
+                if let Some((key, _)) = x {
+                    last = key;
+                }
+                assert_eq!(x.map(|e| e.0), naive.keys().next().map(|e| e.0));
+                if let Some(e) = x {
+                    naive.remove(&e).unwrap();
+                }
+            }
+            assert_eq!(heap.len(), naive.len());
+        }
+    }
+
+    #[test]
+    fn interval_heap_random() {
+        let mut rand = Pcg::seed_from_u64(3403);
+        let mut heap = IntervalHeap::new();
+        let mut naive = BTreeMap::new();
+        for _ in 0..5000 {
+            match rand.next_u32() % 5 {
+                0 | 1 => {
+                    let x = rand.next_u32() % 50;
+                    heap.push(x);
+                    insert(&mut naive, x);
+                }
+                2 => {
+                    let x = heap.pop_min();
+                    assert_eq!(x, naive.keys().next().copied());
+                    if let Some(x) = x {
+                        erase(&mut naive, x);
+                    }
+                }
+                3 => {
+                    let x = heap.pop_max();
+                    assert_eq!(x, naive.keys().next_back().copied());
+                    if let Some(x) = x {
+                        erase(&mut naive, x);
+                    }
+                }
+                _ => {
+                    assert_eq!(heap.peek_min(), naive.keys().next());
+                    assert_eq!(heap.peek_max(), naive.keys().next_back());
+                }
+            }
+            assert_eq!(heap.len(), naive.values().sum::<usize>());
+        }
+    }
+
+    #[test]
+    fn removable_heap_random() {
+        let mut rand = Pcg::seed_from_u64(3404);
+        let mut heap = RemovableHeap::new();
+        let mut naive = BTreeMap::new();
+        let mut elems = vec![];
+        for _ in 0..3000 {
+            match rand.next_u32() % 4 {
+                0 | 1 => {
+                    let x = rand.next_u32() % 50;
+                    heap.push(x);
+                    insert(&mut naive, x);
+                    elems.push(x);
+                }
+                2 if !elems.is_empty() => {
+                    let x = elems.swap_remove(rand.next_u32() as usize % elems.len());
+                    heap.remove(x);
+                    erase(&mut naive, x);
+                }
+                _ => {
+                    let x = heap.pop();
+                    assert_eq!(x, naive.keys().next_back().copied());
+                    if let Some(x) = x {
+                        erase(&mut naive, x);
+                        let i = elems.iter().position(|&y| y == x).unwrap();
+                        elems.swap_remove(i);
+                    }
+                }
+            }
+            assert_eq!(heap.peek(), naive.keys().next_back());
+            assert_eq!(heap.len(), elems.len());
+        }
+    }
+}
@@ -0,0 +1,397 @@
+use super::{lazy_seg_tree::Map, Monoid};
+use crate::random::{RngCore, SplitMix};
+use std::{
+    mem,
+    ops::{Bound, RangeBounds},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+type Tree<T, F> = Option<Box<Node<T, F>>>;
+struct Node<T, F> {
+    val: T,
+    // products in order and in reverse order
+    sum: T,
+    rsum: T,
+    lazy: F,
+    rev: bool,
+    size: usize,
+    pri: u64,
+    left: Tree<T, F>,
+    right: Tree<T, F>,
+}
+fn priority() -> u64 {
+    static SEED: AtomicU64 = AtomicU64::new(0);
+    SplitMix::seed_from_u64(SEED.fetch_add(1, Ordering::Relaxed)).next_u64()
+}
+fn size<T, F>(t: &Tree<T, F>) -> usize {
+    t.as_ref().map(|n| n.size).unwrap_or(0)
+}
+fn reverse<T, F>(n: &mut Node<T, F>) {
+    mem::swap(&mut n.sum, &mut n.rsum);
+    n.rev ^= true;
+}
+fn apply<T: Monoid, F: Monoid + Map<T>>(n: &mut Node<T, F>, f: &F) {
+    n.val = f.map(mem::replace(&mut n.val, T::id()));
+    n.sum = f.map(mem::replace(&mut n.sum, T::id()));
+    n.rsum = f.map(mem::replace(&mut n.rsum, T::id()));
+    n.lazy = f.op(&n.lazy);
+}
+fn push<T: Monoid, F: Monoid + Map<T>>(n: &mut Node<T, F>) {
+    if n.rev {
+        mem::swap(&mut n.left, &mut n.right);
+        for c in n.left.iter_mut().chain(n.right.iter_mut()) {
+            reverse(c);
+        }
+        n.rev = false;
+    }
+    let f = mem::replace(&mut n.lazy, F::id());
+    for c in n.left.iter_mut().chain(n.right.iter_mut()) {
+        apply(c, &f);
+    }
+}
+fn update<T: Monoid, F>(n: &mut Node<T, F>) {
+    let id = T::id();
+    let l = n.left.as_ref();
+    let r = n.right.as_ref();
+    n.sum = l.map(|l| &l.sum).unwrap_or(&id).op(&n.val);
+    n.sum = n.sum.op(r.map(|r| &r.sum).unwrap_or(&id));
+    n.rsum = r.map(|r| &r.rsum).unwrap_or(&id).op(&n.val);
+    n.rsum = n.rsum.op(l.map(|l| &l.rsum).unwrap_or(&id));
+    n.size = size(&n.left) + size(&n.right) + 1;
+}
+fn split<T: Monoid, F: Monoid + Map<T>>(t: Tree<T, F>, k: usize) -> (Tree<T, F>, Tree<T, F>) {
+    if let Some(mut n) = t {
+        push(&mut n);
+        let ls = size(&n.left);
+        if k <= ls {
+            let (a, b) = split(n.left.take(), k);
+            n.left = b;
+            update(&mut n);
+            (a, Some(n))
+        } else {
+            let (a, b) = split(n.right.take(), k - ls - 1);
+            n.right = a;
+            update(&mut n);
+            (Some(n), b)
+        }
+    } else {
+        (None, None)
+    }
+}
+fn merge<T: Monoid, F: Monoid + Map<T>>(a: Tree<T, F>, b: Tree<T, F>) -> Tree<T, F> {
+    match (a, b) {
+        (None, b) => b,
+        (a, None) => a,
+        (Some(mut a), Some(mut b)) => {
+            if a.pri > b.pri {
+                push(&mut a);
+                a.right = merge(a.right.take(), Some(b));
+                update(&mut a);
+                Some(a)
+            } else {
+                push(&mut b);
+                b.left = merge(Some(a), b.left.take());
+                update(&mut b);
+                Some(b)
+            }
+        }
+    }
+}
+
+pub struct ImplicitTreap<T, F> {
+    root: Tree<T, F>,
+}
+impl<T: Monoid, F: Monoid + Map<T>> ImplicitTreap<T, F> {
+    pub fn new() -> Self {
+        Self { root: None }
+    }
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+    fn conv_range<R: RangeBounds<usize>>(&self, range: R) -> (usize, usize) {
+        let l = match range.start_bound() {
+            Bound::Included(i) => *i,
+            Bound::Excluded(i) => i + 1,
+            Bound::Unbounded => 0,
+        };
+        let r = match range.end_bound() {
+            Bound::Included(i) => i + 1,
+            Bound::Excluded(i) => *i,
+            Bound::Unbounded => self.len(),
+        };
+        assert!(l <= r);
+        assert!(r <= self.len());
+        (l, r)
+    }
+    // runs f on the tree of [l, r)
+    fn with_range<R: RangeBounds<usize>, U, G: FnOnce(&mut Tree<T, F>) -> U>(
+        &mut self,
+        range: R,
+        f: G,
+    ) -> U {
+        let (l, r) = self.conv_range(range);
+        let (a, bc) = split(self.root.take(), l);
+        let (mut b, c) = split(bc, r - l);
+        let res = f(&mut b);
+        self.root = merge(merge(a, b), c);
+        res
+    }
+    pub fn insert(&mut self, i: usize, x: T) {
+        assert!(i <= self.len());
+        let mut node = Box::new(Node {
+            val: x,
+            sum: T::id(),
+            rsum: T::id(),
+            lazy: F::id(),
+            rev: false,
+            size: 1,
+            pri: priority(),
+            left: None,
+            right: None,
+        });
+        update(&mut node);
+        let (a, b) = split(self.root.take(), i);
+        self.root = merge(merge(a, Some(node)), b);
+    }
+    pub fn push(&mut self, x: T) {
+        self.insert(self.len(), x);
+    }
+    pub fn remove(&mut self, i: usize) -> T {
+        assert!(i < self.len());
+        let (a, bc) = split(self.root.take(), i);
+        let (b, c) = split(bc, 1);
+        self.root = merge(a, c);
+        b.unwrap().val
+    }
+    pub fn get(&mut self, i: usize) -> &T {
+        assert!(i < self.len());
+        let mut i = i;
+        let mut t = self.root.as_mut().unwrap();
+        loop {
+            push(t);
+            let ls = size(&t.left);
+            if i < ls {
+                t = t.left.as_mut().unwrap();
+            } else if i == ls {
+                return &t.val;
+            } else {
+                i -= ls + 1;
+                t = t.right.as_mut().unwrap();
+            }
+        }
+    }
+    pub fn set(&mut self, i: usize, x: T) {
+        self.with_range(i..=i, |t| {
+            let n = t.as_mut().unwrap();
+            n.val = x;
+            update(n);
+        });
+    }
+    pub fn prod<R: RangeBounds<usize>>(&mut self, range: R) -> T {
+        self.with_range(range, |t| {
+            t.as_ref().map(|n| T::id().op(&n.sum)).unwrap_or_else(T::id)
+        })
+    }
+    pub fn apply<R: RangeBounds<usize>>(&mut self, range: R, f: &F) {
+        self.with_range(range, |t| {
+            if let Some(n) = t {
+                apply(n, f);
+            }
+        });
+    }
+    pub fn reverse<R: RangeBounds<usize>>(&mut self, range: R) {
+        self.with_range(range, |t| {
+            if let Some(n) = t {
+                reverse(n);
+            }
+        });
+    }
+    // moves the elements from the k-th on to the returned sequence
+    pub fn split_at(&mut self, k: usize) -> Self {
+        assert!(k <= self.len());
+        let (a, b) = split(self.root.take(), k);
+        self.root = a;
+        Self { root: b }
+    }
+    pub fn merge(&mut self, other: Self) {
+        let mut other = other;
+        self.root = merge(self.root.take(), other.root.take());
+    }
+    pub fn to_vec(&mut self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        fn rec<T: Monoid + Clone, F: Monoid + Map<T>>(t: &mut Tree<T, F>, res: &mut Vec<T>) {
+            if let Some(n) = t {
+                push(n);
+                rec(&mut n.left, res);
+                res.push(n.val.clone());
+                rec(&mut n.right, res);
+            }
+        }
+        let mut res = Vec::with_capacity(self.len());
+        rec(&mut self.root, &mut res);
+        res
+    }
+}
+impl<T: Monoid, F: Monoid + Map<T>> Default for ImplicitTreap<T, F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<T: Monoid, F: Monoid + Map<T>> std::iter::FromIterator<T> for ImplicitTreap<T, F> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut res = Self::new();
+        for x in iter {
+            res.push(x);
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::*;
+
+    // x -> a x + b, composed left to right
+    #[derive(Clone, Copy, PartialEq, Debug)]
+    struct Affine(u64, u64);
+    impl Monoid for Affine {
+        fn id() -> Self {
+            Affine(1, 0)
+        }
+        fn op(&self, other: &Self) -> Self {
+            Affine(
+                self.0.wrapping_mul(other.0),
+                self.1.wrapping_mul(other.0).wrapping_add(other.1),
+            )
+        }
+    }
+    struct Nop;
+    impl Monoid for Nop {
+        fn id() -> Self {
+            Nop
+        }
+        fn op(&self, _: &Self) -> Self {
+            Nop
+        }
+    }
+    impl Map<Affine> for Nop {
+        fn map(&self, x: Affine) -> Affine {
+            x
+        }
+    }
+
+    #[test]
+    fn implicit_treap_reverse_non_commutative() {
+        let mut rand = Pcg::seed_from_u64(3301);
+        let mut a: Vec<_> = (0..10)
+            .map(|_| Affine(rand.next_u64(), rand.next_u64()))
+            .collect();
+        let mut t: ImplicitTreap<_, Nop> = a.iter().copied().collect();
+        for _ in 0..500 {
+            let n = a.len();
+            let l = rand.next_u32() as usize % (n + 1);
+            let r = l + rand.next_u32() as usize % (n + 1 - l);
+            match rand.next_u32() % 5 {
+                0 => {
+                    let x = Affine(rand.next_u64(), rand.next_u64());
+                    t.insert(l, x);
+                    a.insert(l, x);
+                }
+                1 if l < n => assert_eq!(t.remove(l), a.remove(l)),
+                2 => {
+                    t.reverse(l..r);
+                    a[l..r].reverse();
+                }
+                3 if l < n => {
+                    assert_eq!(*t.get(l), a[l]);
+                    let x = Affine(rand.next_u64(), rand.next_u64());
+                    t.set(l, x);
+                    a[l] = x;
+                }
+                _ => {
+                    let p = a[l..r].iter().fold(Affine::id(), |x, y| x.op(y));
+                    assert_eq!(t.prod(l..r), p);
+                }
+            }
+            assert_eq!(t.len(), a.len());
+        }
+        assert_eq!(t.to_vec(), a);
+        let k = a.len() / 3;
+        let mut u = t.split_at(k);
+        assert_eq!(t.to_vec(), a[..k]);
+        assert_eq!(u.to_vec(), a[k..]);
+        u.merge(t);
+        a.rotate_left(k);
+        assert_eq!(u.to_vec(), a);
+    }
+
+    #[test]
+    fn implicit_treap_range_affine_range_sum() {
+        // (sum, len)
+        #[derive(Clone, Copy, PartialEq, Debug)]
+        struct Sum(u64, u64);
+        impl Monoid for Sum {
+            fn id() -> Self {
+                Sum(0, 0)
+            }
+            fn op(&self, other: &Self) -> Self {
+                Sum(self.0.wrapping_add(other.0), self.1 + other.1)
+            }
+        }
+        // x -> a x + b, where f.op(g) applies g first
+        struct Lin(u64, u64);
+        impl Monoid for Lin {
+            fn id() -> Self {
+                Lin(1, 0)
+            }
+            fn op(&self, other: &Self) -> Self {
+                Lin(
+                    self.0.wrapping_mul(other.0),
+                    self.0.wrapping_mul(other.1).wrapping_add(self.1),
+                )
+            }
+        }
+        impl Map<Sum> for Lin {
+            fn map(&self, x: Sum) -> Sum {
+                Sum(
+                    self.0
+                        .wrapping_mul(x.0)
+                        .wrapping_add(self.1.wrapping_mul(x.1)),
+                    x.1,
+                )
+            }
+        }
+        let mut rand = Pcg::seed_from_u64(3302);
+        let mut a: Vec<u64> = (0..30).map(|_| rand.next_u32() as u64).collect();
+        let mut t: ImplicitTreap<_, Lin> = a.iter().map(|&x| Sum(x, 1)).collect();
+        for _ in 0..500 {
+            let n = a.len();
+            let l = rand.next_u32() as usize % (n + 1);
+            let r = l + rand.next_u32() as usize % (n + 1 - l);
+            match rand.next_u32() % 4 {
+                0 => {
+                    let f = Lin(rand.next_u64(), rand.next_u64());
+                    t.apply(l..r, &f);
+                    for x in &mut a[l..r] {
+                        *x = f.0.wrapping_mul(*x).wrapping_add(f.1);
+                    }
+                }
+                1 => {
+                    t.reverse(l..r);
+                    a[l..r].reverse();
+                }
+                2 if l < n => assert_eq!(t.get(l).0, a[l]),
+                _ => {
+                    let s = a[l..r].iter().fold(0u64, |s, x| s.wrapping_add(*x));
+                    assert_eq!(t.prod(l..r), Sum(s, (r - l) as u64));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,188 @@
+type Line = (i64, i64);
+struct Node {
+    line: Option<Line>,
+    ch: [usize; 2],
+}
+/// Lines `a x + b` over integer `x` in `[lo, hi)`; values are evaluated in `i128`.
+pub struct LiChaoTree {
+    nodes: Vec<Node>,
+    lo: i64,
+    hi: i64,
+    max: bool,
+}
+impl LiChaoTree {
+    pub fn new_min(lo: i64, hi: i64) -> Self {
+        Self::new(lo, hi, false)
+    }
+    pub fn new_max(lo: i64, hi: i64) -> Self {
+        Self::new(lo, hi, true)
+    }
+    fn new(lo: i64, hi: i64, max: bool) -> Self {
+        assert!(lo < hi);
+        Self {
+            nodes: vec![Node {
+                line: None,
+                ch: [!0; 2],
+            }],
+            lo,
+            hi,
+            max,
+        }
+    }
+    fn eval(&self, (a, b): Line, x: i64) -> i128 {
+        let y = a as i128 * x as i128 + b as i128;
+        if self.max {
+            -y
+        } else {
+            y
+        }
+    }
+    fn mid(l: i64, r: i64) -> i64 {
+        ((l as i128 + r as i128) >> 1) as i64
+    }
+    fn child(&mut self, i: usize, d: usize) -> usize {
+        if self.nodes[i].ch[d] == !0 {
+            self.nodes[i].ch[d] = self.nodes.len();
+            self.nodes.push(Node {
+                line: None,
+                ch: [!0; 2],
+            });
+        }
+        self.nodes[i].ch[d]
+    }
+    pub fn add_line(&mut self, a: i64, b: i64) {
+        self.insert(0, self.lo, self.hi, (a, b));
+    }
+    // [l, r)
+    pub fn add_segment(&mut self, a: i64, b: i64, l: i64, r: i64) {
+        let l = l.max(self.lo);
+        let r = r.min(self.hi);
+        if l < r {
+            self.insert_segment(0, self.lo, self.hi, l, r, (a, b));
+        }
+    }
+    fn insert_segment(&mut self, i: usize, l: i64, r: i64, sl: i64, sr: i64, line: Line) {
+        if sr <= l || r <= sl {
+            return;
+        }
+        if sl <= l && r <= sr {
+            self.insert(i, l, r, line);
+            return;
+        }
+        let m = Self::mid(l, r);
+        let c = self.child(i, 0);
+        self.insert_segment(c, l, m, sl, sr, line);
+        let c = self.child(i, 1);
+        self.insert_segment(c, m, r, sl, sr, line);
+    }
+    fn insert(&mut self, mut i: usize, mut l: i64, mut r: i64, mut line: Line) {
+        loop {
+            let cur = if let Some(cur) = self.nodes[i].line {
+                cur
+            } else {
+                self.nodes[i].line = Some(line);
+                return;
+            };
+            let m = Self::mid(l, r);
+            let left = self.eval(line, l) < self.eval(cur, l);
+            let mid = self.eval(line, m) < self.eval(cur, m);
+            if mid {
+                self.nodes[i].line = Some(line);
+                line = cur;
+            }
+            if l + 1 == r {
+                return;
+            }
+            if left != mid {
+                i = self.child(i, 0);
+                r = m;
+            } else {
+                i = self.child(i, 1);
+                l = m;
+            }
+        }
+    }
+    pub fn query(&self, x: i64) -> Option<i128> {
+        assert!(self.lo <= x && x < self.hi);
+        let mut i = 0;
+        let mut l = self.lo;
+        let mut r = self.hi;
+        let mut res = None;
+        while i != !0 {
+            if let Some(line) = self.nodes[i].line {
+                let y = self.eval(line, x);
+                if res.map(|z| y < z).unwrap_or(true) {
+                    res = Some(y);
+                }
+            }
+            let m = Self::mid(l, r);
+            i = if x < m {
+                r = m;
+                self.nodes[i].ch[0]
+            } else {
+                l = m;
+                self.nodes[i].ch[1]
+            };
+        }
+        res.map(|y| if self.max { -y } else { y })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::*;
+
+    #[test]
+    fn li_chao_tree_segments() {
+        const L: i64 = -50;
+        const R: i64 = 50;
+        let mut rand = Pcg::seed_from_u64(2604);
+        for &max in &[false, true] {
+            let mut lct = if max {
+                LiChaoTree::new_max(L, R)
+            } else {
+                LiChaoTree::new_min(L, R)
+            };
+            let mut naive: Vec<Option<i128>> = vec![None; (R - L) as usize];
+            for _ in 0..200 {
+                let a = (rand.next_u32() % 2001) as i64 - 1000;
+                let b = (rand.next_u32() % 2001) as i64 - 1000;
+                let (l, r) = if rand.next_u32() & 1 == 0 {
+                    lct.add_line(a, b);
+                    (L, R)
+                } else {
+                    let l = L + (rand.next_u32() % (R - L) as u32) as i64;
+                    let r = l + 1 + (rand.next_u32() % (R - l) as u32) as i64;
+                    lct.add_segment(a, b, l, r);
+                    (l, r)
+                };
+                for x in l..r {
+                    let y = (a * x + b) as i128;
+                    let v = &mut naive[(x - L) as usize];
+                    *v = Some(match *v {
+                        Some(v) if max => v.max(y),
+                        Some(v) => v.min(y),
+                        None => y,
+                    });
+                }
+                for x in L..R {
+                    assert_eq!(lct.query(x), naive[(x - L) as usize]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn li_chao_tree_large() {
+        let mut lct = LiChaoTree::new_min(i64::MIN, i64::MAX);
+        lct.add_line(i64::MAX, i64::MAX);
+        lct.add_line(i64::MIN, 0);
+        let x = i64::MAX - 1;
+        assert_eq!(lct.query(x), Some(i64::MIN as i128 * x as i128));
+        assert_eq!(
+            lct.query(i64::MIN),
+            Some(i64::MAX as i128 * i64::MIN as i128 + i64::MAX as i128)
+        );
+    }
+}
@@ -0,0 +1,423 @@
+use super::sparse_table::SparseTable;
+use crate::graph2::Graph;
+
+// the window is [l, r)
+pub trait Mo {
+    type Output;
+    fn add_left(&mut self, i: usize);
+    fn add_right(&mut self, i: usize);
+    fn remove_left(&mut self, i: usize);
+    fn remove_right(&mut self, i: usize);
+    fn answer(&self) -> Self::Output;
+}
+
+// the position of (x, y) along the Hilbert curve filling [0, n)^2
+fn hilbert(mut x: u64, mut y: u64, n: u64) -> u64 {
+    let mut d = 0;
+    let mut s = n / 2;
+    while s > 0 {
+        let rx = (x & s > 0) as u64;
+        let ry = (y & s > 0) as u64;
+        d += s * s * (3 * rx ^ ry);
+        if ry == 0 {
+            if rx == 1 {
+                x = n - 1 - x;
+                y = n - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        s /= 2;
+    }
+    d
+}
+
+// moves the window through the queries and calls f with the index of each query
+fn run<M: Mo, F: FnMut(&mut M, usize)>(state: &mut M, queries: &[(usize, usize)], mut f: F) {
+    let n = queries
+        .iter()
+        .map(|q| q.1 + 1)
+        .max()
+        .unwrap_or(1)
+        .next_power_of_two() as u64;
+    let mut ord: Vec<_> = (0..queries.len()).collect();
+    ord.sort_by_cached_key(|&i| hilbert(queries[i].0 as u64, queries[i].1 as u64, n));
+    let (mut l, mut r) = (0, 0);
+    for i in ord {
+        let (ql, qr) = queries[i];
+        assert!(ql <= qr);
+        while l > ql {
+            l -= 1;
+            state.add_left(l);
+        }
+        while r < qr {
+            state.add_right(r);
+            r += 1;
+        }
+        while l < ql {
+            state.remove_left(l);
+            l += 1;
+        }
+        while r > qr {
+            r -= 1;
+            state.remove_right(r);
+        }
+        f(state, i);
+    }
+}
+
+pub fn solve<M: Mo>(state: &mut M, queries: &[(usize, usize)]) -> Vec<M::Output> {
+    let mut res: Vec<_> = queries.iter().map(|_| None).collect();
+    run(state, queries, |s, i| res[i] = Some(s.answer()));
+    res.into_iter().map(Option::unwrap).collect()
+}
+
+// Mo's algorithm without removals; the state starts empty
+pub trait RollbackMo {
+    type Output;
+    fn add_left(&mut self, i: usize);
+    fn add_right(&mut self, i: usize);
+    // saves the state
+    fn snapshot(&mut self);
+    // restores the last saved state
+    fn rollback(&mut self);
+    // makes the state empty
+    fn reset(&mut self);
+    fn answer(&self) -> Self::Output;
+}
+
+// the queries are ranges in [0, n)
+pub fn solve_rollback<M: RollbackMo>(
+    state: &mut M,
+    n: usize,
+    queries: &[(usize, usize)],
+) -> Vec<M::Output> {
+    let q = queries.len();
+    let width = ((n as f64 / (q.max(1) as f64).sqrt()) as usize).max(1);
+    let mut ord: Vec<_> = (0..q).collect();
+    ord.sort_by_key(|&i| (queries[i].0 / width, queries[i].1));
+    let mut res: Vec<_> = queries.iter().map(|_| None).collect();
+    let mut it = ord.into_iter().peekable();
+    while let Some(&first) = it.peek() {
+        let b = queries[first].0 / width;
+        let end = (b + 1) * width;
+        state.reset();
+        let mut r = end;
+        while let Some(i) = it.next_if(|&i| queries[i].0 / width == b) {
+            let (ql, qr) = queries[i];
+            assert!(ql <= qr && qr <= n);
+            if qr <= end {
+                state.snapshot();
+                for j in ql..qr {
+                    state.add_right(j);
+                }
+                res[i] = Some(state.answer());
+                state.rollback();
+                continue;
+            }
+            while r < qr {
+                state.add_right(r);
+                r += 1;
+            }
+            state.snapshot();
+            for j in (ql..end).rev() {
+                state.add_left(j);
+            }
+            res[i] = Some(state.answer());
+            state.rollback();
+        }
+    }
+    res.into_iter().map(Option::unwrap).collect()
+}
+
+// Mo's algorithm over the vertex sets of paths
+pub trait TreeMo {
+    type Output;
+    fn add(&mut self, v: usize);
+    fn remove(&mut self, v: usize);
+    fn answer(&self) -> Self::Output;
+}
+
+// each vertex appears twice in the tour and counts only when inside the window once
+struct Toggle<'a, M> {
+    inner: &'a mut M,
+    tour: Vec<usize>,
+    on: Vec<bool>,
+}
+impl<M: TreeMo> Toggle<'_, M> {
+    fn toggle(&mut self, i: usize) {
+        let v = self.tour[i];
+        if self.on[v] {
+            self.inner.remove(v);
+        } else {
+            self.inner.add(v);
+        }
+        self.on[v] ^= true;
+    }
+}
+impl<M: TreeMo> Mo for Toggle<'_, M> {
+    type Output = M::Output;
+    fn add_left(&mut self, i: usize) {
+        self.toggle(i);
+    }
+    fn add_right(&mut self, i: usize) {
+        self.toggle(i);
+    }
+    fn remove_left(&mut self, i: usize) {
+        self.toggle(i);
+    }
+    fn remove_right(&mut self, i: usize) {
+        self.toggle(i);
+    }
+    fn answer(&self) -> M::Output {
+        self.inner.answer()
+    }
+}
+
+// g is a tree; the queries are the paths between (u, v) including both ends
+pub fn solve_tree<'a, T: 'a, G: Graph<'a, T> + ?Sized, M: TreeMo>(
+    state: &mut M,
+    g: &'a G,
+    root: usize,
+    queries: &[(usize, usize)],
+) -> Vec<M::Output> {
+    let n = g.len();
+    let mut tin = vec![0; n];
+    let mut tout = vec![0; n];
+    let mut par = vec![!0; n];
+    let mut depth = vec![0; n];
+    let mut pre_idx = vec![0; n];
+    let mut pre = Vec::with_capacity(n);
+    let mut tour = Vec::with_capacity(2 * n);
+    let mut stack = vec![root];
+    while let Some(u) = stack.pop() {
+        if u as isize >= 0 {
+            tin[u] = tour.len();
+            tour.push(u);
+            pre_idx[u] = pre.len();
+            pre.push((depth[u], u));
+            stack.push(!u);
+            for v in g.adj_unlabeled(u) {
+                if v != par[u] {
+                    par[v] = u;
+                    depth[v] = depth[u] + 1;
+                    stack.push(v);
+                }
+            }
+        } else {
+            tout[!u] = tour.len();
+            tour.push(!u);
+        }
+    }
+    let st: SparseTable<(usize, usize)> = SparseTable::new(pre);
+    let lca = |u: usize, v: usize| {
+        if u == v {
+            return u;
+        }
+        let (a, b) = (pre_idx[u].min(pre_idx[v]), pre_idx[u].max(pre_idx[v]));
+        par[st.min(a + 1, b + 1).1]
+    };
+    let mut extra = vec![!0; queries.len()];
+    let ranges: Vec<_> = queries
+        .iter()
+        .enumerate()
+        .map(|(i, &(u, v))| {
+            let (u, v) = if tin[u] <= tin[v] { (u, v) } else { (v, u) };
+            let w = lca(u, v);
+            if w == u {
+                (tin[u], tin[v] + 1)
+            } else {
+                extra[i] = w;
+                (tout[u], tin[v] + 1)
+            }
+        })
+        .collect();
+    let mut toggle = Toggle {
+        inner: state,
+        tour,
+        on: vec![false; n],
+    };
+    let mut res: Vec<_> = queries.iter().map(|_| None).collect();
+    run(&mut toggle, &ranges, |s, i| {
+        if extra[i] != !0 {
+            s.inner.add(extra[i]);
+        }
+        res[i] = Some(s.inner.answer());
+        if extra[i] != !0 {
+            s.inner.remove(extra[i]);
+        }
+    });
+    res.into_iter().map(Option::unwrap).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::*;
+
+    struct Distinct<'a> {
+        a: &'a [usize],
+        cnt: Vec<usize>,
+        kinds: usize,
+        history: Vec<(usize, usize)>,
+    }
+    impl<'a> Distinct<'a> {
+        fn new(a: &'a [usize], m: usize) -> Self {
+            Self {
+                a,
+                cnt: vec![0; m],
+                kinds: 0,
+                history: vec![],
+            }
+        }
+        fn add(&mut self, x: usize) {
+            if self.cnt[x] == 0 {
+                self.kinds += 1;
+            }
+            self.cnt[x] += 1;
+        }
+        fn remove(&mut self, x: usize) {
+            self.cnt[x] -= 1;
+            if self.cnt[x] == 0 {
+                self.kinds -= 1;
+            }
+        }
+    }
+    impl Mo for Distinct<'_> {
+        type Output = usize;
+        fn add_left(&mut self, i: usize) {
+            self.add(self.a[i]);
+        }
+        fn add_right(&mut self, i: usize) {
+            self.add(self.a[i]);
+        }
+        fn remove_left(&mut self, i: usize) {
+            self.remove(self.a[i]);
+        }
+        fn remove_right(&mut self, i: usize) {
+            self.remove(self.a[i]);
+        }
+        fn answer(&self) -> usize {
+            self.kinds
+        }
+    }
+    impl RollbackMo for Distinct<'_> {
+        type Output = usize;
+        fn add_left(&mut self, i: usize) {
+            self.add(self.a[i]);
+            self.history.push((i, self.a[i]));
+        }
+        fn add_right(&mut self, i: usize) {
+            <Self as RollbackMo>::add_left(self, i);
+        }
+        fn snapshot(&mut self) {
+            self.history.clear();
+        }
+        fn rollback(&mut self) {
+            while let Some((_, x)) = self.history.pop() {
+                self.remove(x);
+            }
+        }
+        fn reset(&mut self) {
+            self.cnt.iter_mut().for_each(|c| *c = 0);
+            self.kinds = 0;
+            self.history.clear();
+        }
+        fn answer(&self) -> usize {
+            self.kinds
+        }
+    }
+    impl TreeMo for Distinct<'_> {
+        type Output = usize;
+        fn add(&mut self, v: usize) {
+            Distinct::add(self, self.a[v]);
+        }
+        fn remove(&mut self, v: usize) {
+            Distinct::remove(self, self.a[v]);
+        }
+        fn answer(&self) -> usize {
+            self.kinds
+        }
+    }
+
+    fn naive(a: &[usize], l: usize, r: usize) -> usize {
+        let mut b = a[l..r].to_vec();
+        b.sort();
+        b.dedup();
+        b.len()
+    }
+
+    #[test]
+    fn mo_distinct() {
+        let mut rand = Pcg::seed_from_u64(3901);
+        let n = 200;
+        let a: Vec<_> = (0..n).map(|_| rand.next_u32() as usize % 30).collect();
+        let queries: Vec<_> = (0..300)
+            .map(|_| {
+                let l = rand.next_u32() as usize % (n + 1);
+                (l, l + rand.next_u32() as usize % (n + 1 - l))
+            })
+            .collect();
+        let expected: Vec<_> = queries.iter().map(|&(l, r)| naive(&a, l, r)).collect();
+        assert_eq!(solve(&mut Distinct::new(&a, 30), &queries), expected);
+        assert_eq!(
+            solve_rollback(&mut Distinct::new(&a, 30), n, &queries),
+            expected
+        );
+    }
+
+    #[test]
+    fn mo_on_tree_distinct() {
+        let mut rand = Pcg::seed_from_u64(3902);
+        let n = 100;
+        let color: Vec<_> = (0..n).map(|_| rand.next_u32() as usize % 10).collect();
+        let par: Vec<_> = (0..n)
+            .map(|v| {
+                if v == 0 {
+                    !0
+                } else {
+                    rand.next_u32() as usize % v
+                }
+            })
+            .collect();
+        let mut g = crate::graph::Graph::builder(n);
+        g.extend_bi_edges((1..n).map(|v| (v, par[v])));
+        let g = g.build();
+        let queries: Vec<_> = (0..200)
+            .map(|_| {
+                let u = rand.next_u32() as usize % n;
+                (u, rand.next_u32() as usize % n)
+            })
+            .collect();
+        let path = |mut u: usize, mut v: usize| {
+            let depth = |mut w: usize| {
+                let mut d = 0;
+                while w != 0 {
+                    w = par[w];
+                    d += 1;
+                }
+                d
+            };
+            let mut res = vec![];
+            while u != v {
+                if depth(u) < depth(v) {
+                    std::mem::swap(&mut u, &mut v);
+                }
+                res.push(color[u]);
+                u = par[u];
+            }
+            res.push(color[u]);
+            res
+        };
+        let expected: Vec<_> = queries
+            .iter()
+            .map(|&(u, v)| {
+                let p = path(u, v);
+                naive(&p, 0, p.len())
+            })
+            .collect();
+        assert_eq!(
+            solve_tree(&mut Distinct::new(&color, 10), &g, 0, &queries),
+            expected
+        );
+    }
+}
@@ -1,12 +1,19 @@
 pub mod binary_trie;
+pub mod convex_hull_trick;
 pub mod dsu;
 pub mod fenwick_tree;
+pub mod heap;
+pub mod implicit_treap;
 pub mod lazy_seg_tree;
+pub mod li_chao_tree;
+pub mod mo;
+pub mod rect_sum;
 mod segment_tree;
 pub use segment_tree::*;
 pub mod bit_set;
 pub mod sparse_table;
 pub mod splay_tree;
+pub mod sqrtvec;
 pub mod swag;
 pub mod wavelet_matrix;
 
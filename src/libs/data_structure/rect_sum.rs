@@ -0,0 +1,250 @@
+use super::fenwick_tree::FenwickTree;
+use crate::other::coord_comp::CoordinateComp;
+use std::ops::{Add, Range, Sub};
+
+fn lower<T: Ord>(a: &[T], x: &T) -> usize {
+    a.partition_point(|y| y < x)
+}
+
+// merge sort tree over the points sorted by x
+pub struct StaticRectSum<X, Y, W> {
+    xs: Vec<X>,
+    // ys sorted and the prefix sums of the weights in that order
+    tree: Vec<(Vec<Y>, Vec<W>)>,
+}
+impl<X, Y, W> StaticRectSum<X, Y, W>
+where
+    X: Ord + Copy,
+    Y: Ord + Copy,
+    W: Copy + Default + Add<Output = W> + Sub<Output = W>,
+{
+    pub fn new(points: &[(X, Y, W)]) -> Self {
+        let mut ps = points.to_vec();
+        ps.sort_by_key(|p| p.0);
+        let n = ps.len();
+        let mut ys: Vec<Vec<(Y, W)>> = vec![Vec::new(); 2 * n];
+        for (i, &(_, y, w)) in ps.iter().enumerate() {
+            ys[n + i].push((y, w));
+        }
+        for i in (1..n).rev() {
+            let mut v = [&ys[2 * i][..], &ys[2 * i + 1][..]].concat();
+            v.sort_by_key(|e| e.0);
+            ys[i] = v;
+        }
+        let tree = ys
+            .into_iter()
+            .map(|v| {
+                let pre = std::iter::once(W::default())
+                    .chain(v.iter().scan(W::default(), |s, e| {
+                        *s = *s + e.1;
+                        Some(*s)
+                    }))
+                    .collect();
+                (v.into_iter().map(|e| e.0).collect(), pre)
+            })
+            .collect();
+        Self {
+            xs: ps.into_iter().map(|p| p.0).collect(),
+            tree,
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.xs.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.xs.is_empty()
+    }
+    fn fold<F: FnMut(&(Vec<Y>, Vec<W>))>(&self, x: Range<X>, mut f: F) {
+        let n = self.len();
+        let mut l = lower(&self.xs, &x.start) + n;
+        let mut r = lower(&self.xs, &x.end).max(l - n) + n;
+        while l < r {
+            if l & 1 == 1 {
+                f(&self.tree[l]);
+                l += 1;
+            }
+            if r & 1 == 1 {
+                r -= 1;
+                f(&self.tree[r]);
+            }
+            l /= 2;
+            r /= 2;
+        }
+    }
+    // the total weight in [x.start, x.end) * [y.start, y.end)
+    pub fn sum(&self, x: Range<X>, y: Range<Y>) -> W {
+        let mut res = W::default();
+        self.fold(x, |(ys, pre)| {
+            let a = lower(ys, &y.start);
+            let b = lower(ys, &y.end).max(a);
+            res = res + (pre[b] - pre[a]);
+        });
+        res
+    }
+    pub fn count(&self, x: Range<X>, y: Range<Y>) -> usize {
+        let mut res = 0;
+        self.fold(x, |(ys, _)| {
+            res += lower(ys, &y.end).saturating_sub(lower(ys, &y.start));
+        });
+        res
+    }
+}
+
+// answers the rectangle sums of the queries by sweeping x
+pub fn offline_rect_sum<X, Y, W>(points: &[(X, Y, W)], queries: &[(Range<X>, Range<Y>)]) -> Vec<W>
+where
+    X: Ord + Copy,
+    Y: Ord + Copy,
+    W: Copy + Default + Add<Output = W> + Sub<Output = W>,
+{
+    let ys: CoordinateComp<Y> = points.iter().map(|p| p.1).collect();
+    let mut ps = points.to_vec();
+    ps.sort_by_key(|p| p.0);
+    // (x, query, whether to add)
+    let mut events = Vec::with_capacity(2 * queries.len());
+    for (i, (x, _)) in queries.iter().enumerate() {
+        if x.start < x.end {
+            events.push((x.start, i, false));
+            events.push((x.end, i, true));
+        }
+    }
+    events.sort_by_key(|e| e.0);
+    let mut ft = FenwickTree::new(ys.len(), W::default, |a: &W, b: &W| *a + *b);
+    let mut res = vec![W::default(); queries.len()];
+    let mut k = 0;
+    for (x, i, add) in events {
+        while k < ps.len() && ps[k].0 < x {
+            ft.add(ys.lower_bound(&ps[k].1), ps[k].2);
+            k += 1;
+        }
+        let y = &queries[i].1;
+        let a = ys.lower_bound(&y.start);
+        let b = ys.lower_bound(&y.end).max(a);
+        let s = ft.sum(b) - ft.sum(a);
+        res[i] = if add { res[i] + s } else { res[i] - s };
+    }
+    res
+}
+
+// point-add rectangle-sum over points registered in advance; a Fenwick tree over x
+// whose nodes are Fenwick trees over the ys they cover
+pub struct RectSumFenwick<X, Y, W> {
+    xs: CoordinateComp<X>,
+    nodes: Vec<(Vec<Y>, Vec<W>)>,
+}
+impl<X, Y, W> RectSumFenwick<X, Y, W>
+where
+    X: Ord + Copy,
+    Y: Ord + Copy,
+    W: Copy + Default + Add<Output = W> + Sub<Output = W>,
+{
+    pub fn new(points: &[(X, Y)]) -> Self {
+        let xs: CoordinateComp<X> = points.iter().map(|p| p.0).collect();
+        let n = xs.len();
+        let mut ys = vec![Vec::new(); n];
+        for &(x, y) in points {
+            let mut i = xs.lower_bound(&x) + 1;
+            while i <= n {
+                ys[i - 1].push(y);
+                i += i & i.wrapping_neg();
+            }
+        }
+        let nodes = ys
+            .into_iter()
+            .map(|mut v| {
+                v.sort();
+                v.dedup();
+                let m = v.len();
+                (v, vec![W::default(); m + 1])
+            })
+            .collect();
+        Self { xs, nodes }
+    }
+    // (x, y) must be one of the registered points
+    pub fn add(&mut self, x: X, y: Y, w: W) {
+        let mut i = self.xs.position(&x).expect("unregistered point") + 1;
+        while i <= self.nodes.len() {
+            let (ys, bit) = &mut self.nodes[i - 1];
+            let mut j = lower(ys, &y) + 1;
+            assert!(j <= ys.len() && ys[j - 1] == y, "unregistered point");
+            while j < bit.len() {
+                bit[j] = bit[j] + w;
+                j += j & j.wrapping_neg();
+            }
+            i += i & i.wrapping_neg();
+        }
+    }
+    // the points with the x-index less than i and y in y
+    fn prefix(&self, mut i: usize, y: &Range<Y>) -> W {
+        let mut res = W::default();
+        while i > 0 {
+            let (ys, bit) = &self.nodes[i - 1];
+            let a = lower(ys, &y.start);
+            let b = lower(ys, &y.end).max(a);
+            let mut s = W::default();
+            let mut j = b;
+            while j > 0 {
+                s = s + bit[j];
+                j -= j & j.wrapping_neg();
+            }
+            let mut j = a;
+            while j > 0 {
+                s = s - bit[j];
+                j -= j & j.wrapping_neg();
+            }
+            res = res + s;
+            i -= i & i.wrapping_neg();
+        }
+        res
+    }
+    pub fn sum(&self, x: Range<X>, y: Range<Y>) -> W {
+        let l = self.xs.lower_bound(&x.start);
+        let r = self.xs.lower_bound(&x.end).max(l);
+        self.prefix(r, &y) - self.prefix(l, &y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::*;
+
+    #[test]
+    fn rect_sum_random() {
+        let mut rand = Pcg::seed_from_u64(4001);
+        let mut coord = || (rand.next_u32() % 40) as i64 - 20;
+        let points: Vec<(i64, i64, i64)> = (0..200).map(|_| (coord(), coord(), coord())).collect();
+        let queries: Vec<_> = (0..300)
+            .map(|_| (coord()..coord(), coord()..coord()))
+            .collect();
+        let naive = |ps: &[(i64, i64, i64)], x: &Range<i64>, y: &Range<i64>| {
+            ps.iter()
+                .filter(|p| x.contains(&p.0) && y.contains(&p.1))
+                .map(|p| p.2)
+                .sum::<i64>()
+        };
+        let expected: Vec<_> = queries.iter().map(|(x, y)| naive(&points, x, y)).collect();
+
+        let st = StaticRectSum::new(&points);
+        for ((x, y), &e) in queries.iter().zip(&expected) {
+            assert_eq!(st.sum(x.clone(), y.clone()), e);
+            let cnt = points
+                .iter()
+                .filter(|p| x.contains(&p.0) && y.contains(&p.1))
+                .count();
+            assert_eq!(st.count(x.clone(), y.clone()), cnt);
+        }
+
+        assert_eq!(offline_rect_sum(&points, &queries), expected);
+
+        let pos: Vec<_> = points.iter().map(|p| (p.0, p.1)).collect();
+        let mut fw = RectSumFenwick::new(&pos);
+        let mut added = vec![];
+        for (i, &p) in points.iter().enumerate() {
+            fw.add(p.0, p.1, p.2);
+            added.push(p);
+            let (x, y) = &queries[i];
+            assert_eq!(fw.sum(x.clone(), y.clone()), naive(&added, x, y));
+        }
+    }
+}
@@ -1,5 +1,81 @@
-pub struct SparseTable<T>(Box<[Box<[T]>]>);
-impl<T: Ord + Clone> SparseTable<T> {
+use std::marker::PhantomData;
+pub trait Operation<T> {
+    fn op(x: &T, y: &T) -> T;
+}
+/// Marker for operations with `op(x, x) == x`, required by `SparseTable`.
+pub trait Idempotent<T>: Operation<T> {}
+pub struct Min;
+impl<T: Ord + Clone> Operation<T> for Min {
+    fn op(x: &T, y: &T) -> T {
+        x.min(y).clone()
+    }
+}
+impl<T: Ord + Clone> Idempotent<T> for Min {}
+pub struct Max;
+impl<T: Ord + Clone> Operation<T> for Max {
+    fn op(x: &T, y: &T) -> T {
+        x.max(y).clone()
+    }
+}
+impl<T: Ord + Clone> Idempotent<T> for Max {}
+pub struct And;
+impl<T: Copy + std::ops::BitAnd<Output = T>> Operation<T> for And {
+    fn op(x: &T, y: &T) -> T {
+        *x & *y
+    }
+}
+impl<T: Copy + std::ops::BitAnd<Output = T>> Idempotent<T> for And {}
+pub struct Or;
+impl<T: Copy + std::ops::BitOr<Output = T>> Operation<T> for Or {
+    fn op(x: &T, y: &T) -> T {
+        *x | *y
+    }
+}
+impl<T: Copy + std::ops::BitOr<Output = T>> Idempotent<T> for Or {}
+pub struct Gcd;
+macro_rules! gcd_impl {
+    ($($T:ident)*) => {$(
+        impl Operation<$T> for Gcd {
+            fn op(x: &$T, y: &$T) -> $T {
+                let (mut x, mut y) = (*x, *y);
+                while y != 0 {
+                    x %= y;
+                    std::mem::swap(&mut x, &mut y);
+                }
+                x
+            }
+        }
+        impl Idempotent<$T> for Gcd {}
+    )*};
+}
+gcd_impl!(u8 u16 u32 u64 u128 usize);
+pub struct Sum;
+impl<T> Operation<T> for Sum
+where
+    for<'a> &'a T: std::ops::Add<&'a T, Output = T>,
+{
+    fn op(x: &T, y: &T) -> T {
+        x + y
+    }
+}
+pub struct Prod;
+impl<T> Operation<T> for Prod
+where
+    for<'a> &'a T: std::ops::Mul<&'a T, Output = T>,
+{
+    fn op(x: &T, y: &T) -> T {
+        x * y
+    }
+}
+pub struct Xor;
+impl<T: Copy + std::ops::BitXor<Output = T>> Operation<T> for Xor {
+    fn op(x: &T, y: &T) -> T {
+        *x ^ *y
+    }
+}
+
+pub struct SparseTable<T, O = Min>(Box<[Box<[T]>]>, PhantomData<O>);
+impl<T: Clone, O: Idempotent<T>> SparseTable<T, O> {
     pub fn new<A: Into<Box<[T]>>>(a: A) -> Self {
         let mut a = a.into();
         let mut tab = Vec::new();
@@ -7,18 +83,29 @@ impl<T: Ord + Clone> SparseTable<T> {
         while width < a.len() {
             let mut b = Vec::with_capacity(a.len() - width);
             for i in width..a.len() {
-                b.push((&a[i - width]).min(&a[i]).clone());
+                b.push(O::op(&a[i - width], &a[i]));
             }
             tab.push(a);
             a = b.into();
             width *= 2;
         }
         tab.push(a);
-        Self(tab.into())
+        Self(tab.into(), PhantomData)
     }
     pub fn len(&self) -> usize {
         self.0.first().map(|a| a.len()).unwrap_or(0)
     }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn fold(&self, l: usize, r: usize) -> T {
+        assert!(l < r);
+        assert!(r <= self.len());
+        let k = 8 * std::mem::size_of::<usize>() - (r - l).leading_zeros() as usize - 1;
+        O::op(&self.0[k][l], &self.0[k][r - (1 << k)])
+    }
+}
+impl<T: Ord + Clone> SparseTable<T, Min> {
     pub fn min(&self, l: usize, r: usize) -> &T {
         assert!(l < r);
         assert!(r <= self.len());
@@ -26,12 +113,64 @@ impl<T: Ord + Clone> SparseTable<T> {
         (&self.0[k][l]).min(&self.0[k][r - (1 << k)])
     }
 }
-impl<T: Ord + Clone> std::iter::FromIterator<T> for SparseTable<T> {
+impl<T: Clone, O: Idempotent<T>> std::iter::FromIterator<T> for SparseTable<T, O> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::new(iter.into_iter().collect::<Box<[T]>>())
+    }
+}
+impl<T, O> std::ops::Deref for SparseTable<T, O> {
+    type Target = [T];
+    fn deref(&self) -> &[T] {
+        self.0.first().unwrap()
+    }
+}
+
+pub struct DisjointSparseTable<T, O>(Box<[Box<[T]>]>, PhantomData<O>);
+impl<T: Clone, O: Operation<T>> DisjointSparseTable<T, O> {
+    pub fn new<A: Into<Box<[T]>>>(a: A) -> Self {
+        let a = a.into();
+        let n = a.len();
+        let mut tab = Vec::new();
+        let mut width = 1;
+        while width < n {
+            let mut b = a.to_vec();
+            for m in (width..n).step_by(2 * width) {
+                for i in (m - width..m - 1).rev() {
+                    b[i] = O::op(&a[i], &b[i + 1]);
+                }
+                for i in m + 1..(m + width).min(n) {
+                    b[i] = O::op(&b[i - 1], &a[i]);
+                }
+            }
+            tab.push(b.into());
+            width *= 2;
+        }
+        tab.insert(0, a);
+        Self(tab.into(), PhantomData)
+    }
+    pub fn len(&self) -> usize {
+        self.0.first().map(|a| a.len()).unwrap_or(0)
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn fold(&self, l: usize, r: usize) -> T {
+        assert!(l < r);
+        assert!(r <= self.len());
+        let r = r - 1;
+        if l == r {
+            return self.0[0][l].clone();
+        }
+        let k = 8 * std::mem::size_of::<usize>() - (l ^ r).leading_zeros() as usize;
+        O::op(&self.0[k][l], &self.0[k][r])
+    }
+}
+impl<T: Clone, O: Operation<T>> std::iter::FromIterator<T> for DisjointSparseTable<T, O> {
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
         Self::new(iter.into_iter().collect::<Box<[T]>>())
     }
 }
-impl<T> std::ops::Deref for SparseTable<T> {
+impl<T, O> std::ops::Deref for DisjointSparseTable<T, O> {
     type Target = [T];
     fn deref(&self) -> &[T] {
         self.0.first().unwrap()
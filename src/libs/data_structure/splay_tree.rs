@@ -1,114 +1,326 @@
+use std::{
+    cmp::Ordering::{self, *},
+    ops::{Bound, RangeBounds},
+};
 pub struct SplayTreeMap<K, V> {
     node: Option<Box<Node<K, V>>>,
 }
 struct Node<K, V> {
     key: K,
     value: V,
+    size: usize,
     left: SplayTreeMap<K, V>,
     right: SplayTreeMap<K, V>,
 }
-impl<K: Ord, V> SplayTreeMap<K, V> {
+impl<K, V> Node<K, V> {
+    fn update(&mut self) {
+        self.size = self.left.len() + self.right.len() + 1;
+    }
+}
+impl<K, V> SplayTreeMap<K, V> {
     pub fn new() -> Self {
         Self { node: None }
     }
-    pub fn get(&mut self, key: &K) -> Option<&V> {
-        self.splay(key);
-        self.node
-            .as_ref()
-            .and_then(|s| if &s.key == key { Some(&s.value) } else { None })
+    pub fn len(&self) -> usize {
+        self.node.as_ref().map(|s| s.size).unwrap_or(0)
     }
-    pub fn insert(&mut self, key: K, value: V) {
-        use std::cmp::Ordering::*;
-        self.splay(&key);
-        let (left, right) = if let Some(s) = self.node.as_mut() {
-            match key.cmp(&s.key) {
-                Equal => {
-                    s.value = value;
-                    return;
-                }
-                Less => (s.left.take(), self.take()),
+    pub fn is_empty(&self) -> bool {
+        self.node.is_none()
+    }
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter {
+            stack: Vec::new(),
+            rest: self.len(),
+        };
+        iter.push_left(self);
+        iter
+    }
+    // moves the k-th smallest entry to the root
+    pub fn nth(&mut self, mut k: usize) -> Option<(&K, &V)> {
+        if k >= self.len() {
+            return None;
+        }
+        self.splay_by(|s| {
+            let l = s.left.len();
+            match k.cmp(&l) {
                 Greater => {
-                    let r = s.right.take();
-                    (self.take(), r)
+                    k -= l + 1;
+                    Greater
                 }
+                c => c,
             }
-        } else {
-            (Self::new(), Self::new())
-        };
-        self.node = Some(Box::new(Node {
-            key,
-            value,
-            left,
-            right,
-        }));
+        });
+        self.node.as_ref().map(|s| (&s.key, &s.value))
+    }
+    // moves every entry from the k-th smallest on to the returned map
+    pub fn split_at(&mut self, k: usize) -> Self {
+        if self.nth(k).is_none() {
+            return Self::new();
+        }
+        let mut res = self.take();
+        if let Some(s) = res.node.as_mut() {
+            *self = s.left.take();
+            s.update();
+        }
+        res
     }
-    fn splay(&mut self, key: &K) {
-        use std::cmp::Ordering::*;
+    // every key of self must be less than every key of other
+    pub fn merge(&mut self, other: Self) {
+        if self.is_empty() {
+            *self = other;
+            return;
+        }
+        self.splay_by(|_| Greater);
         if let Some(s) = self.node.as_mut() {
-            match key.cmp(&s.key) {
+            s.right = other;
+            s.update();
+        }
+    }
+    // top-down splaying of the last node on the path that f leads to
+    fn splay_by<F: FnMut(&Node<K, V>) -> Ordering>(&mut self, mut f: F) {
+        let mut t = match self.node.take() {
+            Some(t) => t,
+            None => return,
+        };
+        // the nodes less than t, each to become the right child of the previous one, and
+        // the nodes greater than t, each to become the left child of the previous one
+        let mut less = Vec::new();
+        let mut greater = Vec::new();
+        let mut c = f(&t);
+        loop {
+            match c {
                 Less => {
-                    if let Some(l) = s.left.node.as_mut() {
-                        match key.cmp(&l.key) {
-                            Less => {
-                                l.left.splay(key);
-                                self.rot_right();
+                    let mut l = match t.left.node.take() {
+                        Some(l) => l,
+                        None => break,
+                    };
+                    let cl = f(&l);
+                    if cl == Less {
+                        t.left = l.right.take();
+                        t.update();
+                        l.right.node = Some(t);
+                        t = l;
+                        match t.left.node.take() {
+                            Some(ll) => {
+                                greater.push(t);
+                                t = ll;
+                                c = f(&t);
                             }
-                            Greater => {
-                                l.right.splay(key);
-                                s.left.rot_left();
-                            }
-                            _ => {}
+                            None => break,
                         }
-                        self.rot_right();
+                    } else {
+                        greater.push(t);
+                        t = l;
+                        c = cl;
                     }
                 }
                 Greater => {
-                    if let Some(r) = s.right.node.as_mut() {
-                        match key.cmp(&r.key) {
-                            Less => {
-                                r.left.splay(key);
-                                s.right.rot_right();
-                            }
-                            Greater => {
-                                r.right.splay(key);
-                                self.rot_left();
+                    let mut r = match t.right.node.take() {
+                        Some(r) => r,
+                        None => break,
+                    };
+                    let cr = f(&r);
+                    if cr == Greater {
+                        t.right = r.left.take();
+                        t.update();
+                        r.left.node = Some(t);
+                        t = r;
+                        match t.right.node.take() {
+                            Some(rr) => {
+                                less.push(t);
+                                t = rr;
+                                c = f(&t);
                             }
-                            _ => {}
+                            None => break,
                         }
-                        self.rot_left();
+                    } else {
+                        less.push(t);
+                        t = r;
+                        c = cr;
                     }
                 }
-                _ => {}
+                Equal => break,
             }
         }
+        let mut l = t.left.take();
+        while let Some(mut p) = less.pop() {
+            p.right = l;
+            p.update();
+            l = Self { node: Some(p) };
+        }
+        let mut r = t.right.take();
+        while let Some(mut p) = greater.pop() {
+            p.left = r;
+            p.update();
+            r = Self { node: Some(p) };
+        }
+        t.left = l;
+        t.right = r;
+        t.update();
+        self.node = Some(t);
     }
     fn take(&mut self) -> Self {
         Self {
             node: self.node.take(),
         }
     }
-    fn replace(&mut self, node: Box<Node<K, V>>) -> Self {
-        Self {
-            node: self.node.replace(node),
-        }
+}
+impl<K: Ord, V> SplayTreeMap<K, V> {
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        self.splay_by(|s| key.cmp(&s.key));
+        self.node
+            .as_ref()
+            .and_then(|s| if &s.key == key { Some(&s.value) } else { None })
     }
-    fn rot_left(&mut self) {
-        if let Some(s) = self.node.as_mut() {
-            if let Some(mut r) = s.right.node.take() {
-                std::mem::swap(s, &mut r);
-                r.right = s.left.take();
-                s.left.replace(r);
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.splay_by(|s| key.cmp(&s.key));
+        self.node.as_mut().and_then(|s| {
+            if &s.key == key {
+                Some(&mut s.value)
+            } else {
+                None
+            }
+        })
+    }
+    pub fn contains_key(&mut self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.splay_by(|s| key.cmp(&s.key));
+        let (left, right) = if let Some(s) = self.node.as_mut() {
+            match key.cmp(&s.key) {
+                Equal => return Some(std::mem::replace(&mut s.value, value)),
+                Less => {
+                    let l = s.left.take();
+                    s.update();
+                    (l, self.take())
+                }
+                Greater => {
+                    let r = s.right.take();
+                    s.update();
+                    (self.take(), r)
+                }
             }
+        } else {
+            (Self::new(), Self::new())
+        };
+        let mut node = Box::new(Node {
+            key,
+            value,
+            size: 1,
+            left,
+            right,
+        });
+        node.update();
+        self.node = Some(node);
+        None
+    }
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        self.splay_by(|s| key.cmp(&s.key));
+        if self.node.as_ref().map(|s| &s.key != key).unwrap_or(true) {
+            return None;
         }
+        let mut s = self.node.take().unwrap();
+        *self = s.left.take();
+        self.merge(s.right.take());
+        Some(s.value)
     }
-    fn rot_right(&mut self) {
-        if let Some(s) = self.node.as_mut() {
-            if let Some(mut l) = s.left.node.take() {
-                std::mem::swap(s, &mut l);
-                l.left = s.right.take();
-                s.right.replace(l);
+    // the number of keys less than key
+    pub fn rank(&mut self, key: &K) -> usize {
+        self.rank_by(|k| k < key)
+    }
+    fn rank_by<F: Fn(&K) -> bool>(&mut self, f: F) -> usize {
+        self.splay_by(|s| if f(&s.key) { Greater } else { Less });
+        self.node
+            .as_ref()
+            .map(|s| s.left.len() + f(&s.key) as usize)
+            .unwrap_or(0)
+    }
+    // the first entry whose key is not less than key
+    pub fn lower_bound(&mut self, key: &K) -> Option<(&K, &V)> {
+        let k = self.rank(key);
+        self.nth(k)
+    }
+    // the first entry whose key is greater than key
+    pub fn upper_bound(&mut self, key: &K) -> Option<(&K, &V)> {
+        let k = self.rank_by(|k| k <= key);
+        self.nth(k)
+    }
+    pub fn range<R: RangeBounds<K>>(&mut self, range: R) -> Iter<'_, K, V> {
+        let r = match range.end_bound() {
+            Bound::Included(x) => self.rank_by(|k| k <= x),
+            Bound::Excluded(x) => self.rank(x),
+            Bound::Unbounded => self.len(),
+        };
+        let l = match range.start_bound() {
+            Bound::Included(x) => self.rank(x),
+            Bound::Excluded(x) => self.rank_by(|k| k <= x),
+            Bound::Unbounded => 0,
+        };
+        let mut iter = Iter {
+            stack: Vec::new(),
+            rest: r.saturating_sub(l),
+        };
+        if iter.rest > 0 {
+            self.nth(l);
+            if let Some(s) = self.node.as_ref() {
+                iter.stack.push(s);
             }
         }
+        iter
+    }
+    // moves every entry with a key not less than key to the returned map
+    pub fn split(&mut self, key: &K) -> Self {
+        let k = self.rank(key);
+        self.split_at(k)
+    }
+}
+impl<K, V> Default for SplayTreeMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<K, V> Drop for SplayTreeMap<K, V> {
+    fn drop(&mut self) {
+        let mut stack: Vec<_> = self.node.take().into_iter().collect();
+        while let Some(mut s) = stack.pop() {
+            stack.extend(s.left.node.take());
+            stack.extend(s.right.node.take());
+        }
+    }
+}
+pub struct Iter<'a, K, V> {
+    stack: Vec<&'a Node<K, V>>,
+    rest: usize,
+}
+impl<'a, K, V> Iter<'a, K, V> {
+    fn push_left(&mut self, mut t: &'a SplayTreeMap<K, V>) {
+        while let Some(s) = t.node.as_ref() {
+            self.stack.push(s);
+            t = &s.left;
+        }
+    }
+}
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest == 0 {
+            return None;
+        }
+        self.rest -= 1;
+        let s = self.stack.pop()?;
+        self.push_left(&s.right);
+        Some((&s.key, &s.value))
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.rest, Some(self.rest))
+    }
+}
+impl<'a, K, V> IntoIterator for &'a SplayTreeMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
     }
 }
 
@@ -127,3 +339,87 @@ impl<K: fmt::Display, V> fmt::Display for SplayTreeMap<K, V> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn splay_tree_map() {
+        const M: u32 = 60;
+        let mut rand = Pcg::seed_from_u64(3201);
+        let mut st = SplayTreeMap::new();
+        let mut bt = BTreeMap::new();
+        for i in 0..2000 {
+            let key = rand.next_u32() % M;
+            match rand.next_u32() % 6 {
+                0 | 1 => assert_eq!(st.insert(key, i), bt.insert(key, i)),
+                2 => assert_eq!(st.remove(&key), bt.remove(&key)),
+                3 => {
+                    assert_eq!(st.get(&key), bt.get(&key));
+                    assert_eq!(st.rank(&key), bt.range(..key).count());
+                    assert_eq!(st.lower_bound(&key), bt.range(key..).next());
+                    assert_eq!(st.upper_bound(&key), bt.range(key + 1..).next());
+                }
+                4 => {
+                    let k = rand.next_u32() as usize % (bt.len() + 1);
+                    assert_eq!(st.nth(k), bt.iter().nth(k));
+                }
+                _ => {
+                    let l = rand.next_u32() % M;
+                    let r = rand.next_u32() % M;
+                    let a: Vec<_> = st.range(l..=r).collect();
+                    let b: Vec<_> = if l <= r {
+                        bt.range(l..=r).collect()
+                    } else {
+                        vec![]
+                    };
+                    assert_eq!(a, b);
+                }
+            }
+            assert_eq!(st.len(), bt.len());
+        }
+        assert!(st.iter().eq(bt.iter()));
+    }
+
+    #[test]
+    fn splay_tree_map_split_merge() {
+        let mut rand = Pcg::seed_from_u64(3202);
+        let mut st = SplayTreeMap::new();
+        for _ in 0..100 {
+            let key = rand.next_u32() % 1000;
+            st.insert(key, key * 2);
+        }
+        let keys: Vec<_> = st.iter().map(|(k, _)| *k).collect();
+        for &x in &[0, 1, 500, 999, 1000] {
+            let mut right = st.split(&x);
+            assert!(st.iter().all(|(k, _)| *k < x));
+            assert!(right.iter().all(|(k, _)| *k >= x));
+            assert_eq!(st.len() + right.len(), keys.len());
+            assert_eq!(
+                right.lower_bound(&x).map(|(k, _)| *k),
+                keys.iter().find(|&&k| k >= x).copied()
+            );
+            st.merge(right);
+            assert!(st.iter().map(|(k, _)| *k).eq(keys.iter().copied()));
+        }
+    }
+
+    #[test]
+    fn splay_tree_map_sequential() {
+        const N: usize = 1_000_000;
+        let mut st = SplayTreeMap::new();
+        for i in 0..N {
+            st.insert(i, ());
+        }
+        assert_eq!(st.len(), N);
+        assert_eq!(st.get(&0), Some(&()));
+        assert_eq!(st.nth(N / 2).map(|(k, _)| *k), Some(N / 2));
+        for i in (0..N).rev() {
+            st.insert(i, ());
+        }
+        assert_eq!(st.rank(&(N - 1)), N - 1);
+    }
+}
@@ -1,69 +1,311 @@
-use std::slice::SliceIndex;
-const SIZE: usize = 256;
+use super::{lazy_seg_tree::Map, Monoid};
+use std::ops::{Bound, RangeBounds};
+
+fn conv_range<R: RangeBounds<usize>>(range: R, n: usize) -> (usize, usize) {
+    let l = match range.start_bound() {
+        Bound::Included(i) => *i,
+        Bound::Excluded(i) => i + 1,
+        Bound::Unbounded => 0,
+    };
+    let r = match range.end_bound() {
+        Bound::Included(i) => i + 1,
+        Bound::Excluded(i) => *i,
+        Bound::Unbounded => n,
+    };
+    assert!(l <= r);
+    assert!(r <= n);
+    (l, r)
+}
+
 #[derive(Clone)]
 enum Block<T> {
     Covered(T),
-    Data([T; SIZE]),
+    Data(Vec<T>),
 }
-impl<T: Copy> Block<T> {
-    fn expand(&mut self) {
-        if let Self::Covered(v) = self {
-            *self = Self::Data([*v; SIZE]);
+impl<T: Clone> Block<T> {
+    fn fill(&mut self, l: usize, r: usize, v: T, size: usize) {
+        if let Self::Covered(c) = self {
+            *self = Self::Data(vec![c.clone(); size]);
         }
-    }
-    fn fill<I: SliceIndex<[T], Output = [T]>>(&mut self, range: I, v: T) {
-        self.expand();
         if let Self::Data(a) = self {
-            for x in &mut a[range] {
-                *x = v;
-            }
+            a[l..r].fill(v);
         }
     }
 }
-struct SqrtVec<T> {
+// vector with range assignment
+#[derive(Clone)]
+pub struct SqrtVec<T> {
     blocks: Vec<Block<T>>,
+    size: usize,
     len: usize,
 }
-impl<T: Copy> SqrtVec<T> {
+impl<T: Clone> SqrtVec<T> {
     pub fn new() -> Self {
+        Self::with_block_size(256)
+    }
+    pub fn with_block_size(size: usize) -> Self {
+        assert!(size > 0);
         Self {
             blocks: Vec::new(),
+            size,
             len: 0,
         }
     }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
     pub fn resize(&mut self, n: usize, v: T) {
-        self.blocks.resize((n + SIZE - 1) / SIZE, Block::Covered(v));
+        let old = self.len;
         self.len = n;
+        self.blocks
+            .resize((n + self.size - 1) / self.size, Block::Covered(v.clone()));
+        if old < n {
+            let end = (old + self.size - 1) / self.size * self.size;
+            self.fill(old..n.min(end), v);
+        }
     }
-    pub fn fill(&mut self, range: std::ops::Range<usize>, v: T) {
-        let ldiv = range.start / SIZE;
-        let lrem = range.start % SIZE;
-        let rdiv = range.end / SIZE;
-        let rrem = range.end % SIZE;
+    pub fn fill<R: RangeBounds<usize>>(&mut self, range: R, v: T) {
+        let (l, r) = conv_range(range, self.len);
+        if l == r {
+            return;
+        }
+        let size = self.size;
+        let ldiv = l / size;
+        let rdiv = (r - 1) / size;
         if ldiv == rdiv {
-            self.blocks[ldiv].fill(lrem..rrem, v);
+            self.blocks[ldiv].fill(l % size, (r - 1) % size + 1, v, size);
         } else {
-            self.blocks[ldiv].fill(lrem.., v);
+            self.blocks[ldiv].fill(l % size, size, v.clone(), size);
             for b in &mut self.blocks[ldiv + 1..rdiv] {
-                *b = Block::Covered(v);
+                *b = Block::Covered(v.clone());
             }
-            self.blocks.get_mut(rdiv).map(|b| b.fill(..rrem, v));
+            self.blocks[rdiv].fill(0, (r - 1) % size + 1, v, size);
         }
     }
     pub fn get(&self, i: usize) -> Option<&T> {
         if i < self.len {
-            self.blocks.get(i / SIZE).map(|b| match b {
+            self.blocks.get(i / self.size).map(|b| match b {
                 Block::Covered(v) => v,
-                Block::Data(a) => &a[i % SIZE],
+                Block::Data(a) => &a[i % self.size],
             })
         } else {
             None
         }
     }
 }
-impl<T: Copy> std::ops::Index<usize> for SqrtVec<T> {
+impl<T: Clone> Default for SqrtVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<T: Clone> std::ops::Index<usize> for SqrtVec<T> {
     type Output = T;
     fn index(&self, i: usize) -> &T {
         self.get(i).unwrap()
     }
-}
\ No newline at end of file
+}
+
+// blocks keep the product of their elements and a lazy map pending on all of them
+pub struct SqrtDecomposition<T, F> {
+    data: Vec<T>,
+    sums: Vec<T>,
+    lazy: Vec<F>,
+    size: usize,
+}
+impl<T: Monoid, F: Monoid + Map<T>> SqrtDecomposition<T, F> {
+    pub fn new(a: Vec<T>, size: usize) -> Self {
+        assert!(size > 0);
+        let nb = (a.len() + size - 1) / size;
+        let mut res = Self {
+            data: a,
+            sums: std::iter::repeat_with(T::id).take(nb).collect(),
+            lazy: std::iter::repeat_with(F::id).take(nb).collect(),
+            size,
+        };
+        for b in 0..nb {
+            res.rebuild(b);
+        }
+        res
+    }
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+    pub fn block_size(&self) -> usize {
+        self.size
+    }
+    fn block(&self, b: usize) -> (usize, usize) {
+        (b * self.size, ((b + 1) * self.size).min(self.len()))
+    }
+    fn push(&mut self, b: usize) {
+        let (l, r) = self.block(b);
+        let f = std::mem::replace(&mut self.lazy[b], F::id());
+        for x in &mut self.data[l..r] {
+            *x = f.map(std::mem::replace(x, T::id()));
+        }
+    }
+    fn rebuild(&mut self, b: usize) {
+        let (l, r) = self.block(b);
+        self.sums[b] = self.data[l..r].iter().fold(T::id(), |s, x| s.op(x));
+    }
+    // (b, l, r, whether [l, r) is the whole block b) for the blocks meeting [l, r)
+    fn blocks(&self, l: usize, r: usize) -> impl Iterator<Item = (usize, usize, usize, bool)> + '_ {
+        let bs = if l == r {
+            0..0
+        } else {
+            l / self.size..(r - 1) / self.size + 1
+        };
+        bs.map(move |b| {
+            let (bl, br) = self.block(b);
+            (b, l.max(bl), r.min(br), l <= bl && br <= r)
+        })
+    }
+    pub fn get(&self, i: usize) -> T
+    where
+        T: Clone,
+    {
+        self.lazy[i / self.size].map(self.data[i].clone())
+    }
+    pub fn set(&mut self, i: usize, x: T) {
+        let b = i / self.size;
+        self.push(b);
+        self.data[i] = x;
+        self.rebuild(b);
+    }
+    pub fn prod<R: RangeBounds<usize>>(&self, range: R) -> T {
+        let (l, r) = conv_range(range, self.len());
+        let mut res = T::id();
+        for (b, l, r, full) in self.blocks(l, r) {
+            if full {
+                res = res.op(&self.sums[b]);
+            } else {
+                let x = self.data[l..r].iter().fold(T::id(), |s, x| s.op(x));
+                res = res.op(&self.lazy[b].map(x));
+            }
+        }
+        res
+    }
+    pub fn apply<R: RangeBounds<usize>>(&mut self, range: R, f: &F) {
+        let (l, r) = conv_range(range, self.len());
+        let blocks: Vec<_> = self.blocks(l, r).collect();
+        for (b, l, r, full) in blocks {
+            if full {
+                self.sums[b] = f.map(std::mem::replace(&mut self.sums[b], T::id()));
+                self.lazy[b] = f.op(&self.lazy[b]);
+            } else {
+                self.push(b);
+                for x in &mut self.data[l..r] {
+                    *x = f.map(std::mem::replace(x, T::id()));
+                }
+                self.rebuild(b);
+            }
+        }
+    }
+}
+impl<T: Monoid, F: Monoid + Map<T>> std::iter::FromIterator<T> for SqrtDecomposition<T, F> {
+    // blocks of about sqrt(n) elements
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let a: Vec<T> = iter.into_iter().collect();
+        let size = ((a.len() as f64).sqrt() as usize).max(1);
+        Self::new(a, size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::*;
+
+    #[test]
+    fn sqrt_vec() {
+        let mut rand = Pcg::seed_from_u64(3701);
+        let mut v = SqrtVec::with_block_size(7);
+        let mut naive = vec![];
+        for _ in 0..1000 {
+            let n = naive.len();
+            let l = rand.next_u32() as usize % (n + 1);
+            let r = l + rand.next_u32() as usize % (n + 1 - l);
+            let x = rand.next_u32() % 100;
+            if rand.next_u32() & 7 == 0 {
+                let m = rand.next_u32() as usize % 60;
+                v.resize(m, x);
+                naive.resize(m, x);
+            } else {
+                v.fill(l..r, x);
+                naive[l..r].fill(x);
+            }
+            assert_eq!(v.len(), naive.len());
+            assert!((0..naive.len()).all(|i| v[i] == naive[i]));
+            assert_eq!(v.get(naive.len()), None);
+        }
+    }
+
+    #[test]
+    fn sqrt_decomposition_assign_add_sum() {
+        // (sum, len)
+        #[derive(Clone, Copy, PartialEq, Debug)]
+        struct Sum(i64, i64);
+        impl Monoid for Sum {
+            fn id() -> Self {
+                Sum(0, 0)
+            }
+            fn op(&self, other: &Self) -> Self {
+                Sum(self.0 + other.0, self.1 + other.1)
+            }
+        }
+        // assigns then adds
+        #[derive(Clone, Copy)]
+        struct Update(Option<i64>, i64);
+        impl Monoid for Update {
+            fn id() -> Self {
+                Update(None, 0)
+            }
+            fn op(&self, other: &Self) -> Self {
+                match self.0 {
+                    Some(_) => *self,
+                    None => Update(other.0, other.1 + self.1),
+                }
+            }
+        }
+        impl Map<Sum> for Update {
+            fn map(&self, x: Sum) -> Sum {
+                let s = self.0.map(|v| v * x.1).unwrap_or(x.0);
+                Sum(s + self.1 * x.1, x.1)
+            }
+        }
+        let mut rand = Pcg::seed_from_u64(3702);
+        let n = 50;
+        let mut naive: Vec<i64> = (0..n).map(|_| (rand.next_u32() % 100) as i64).collect();
+        let mut sd: SqrtDecomposition<_, Update> = naive.iter().map(|&x| Sum(x, 1)).collect();
+        assert_eq!(sd.block_size(), 7);
+        for _ in 0..2000 {
+            let l = rand.next_u32() as usize % (n + 1);
+            let r = l + rand.next_u32() as usize % (n + 1 - l);
+            let x = (rand.next_u32() % 100) as i64 - 50;
+            match rand.next_u32() % 4 {
+                0 => {
+                    sd.apply(l..r, &Update(Some(x), 0));
+                    naive[l..r].fill(x);
+                }
+                1 => {
+                    sd.apply(l..r, &Update(None, x));
+                    naive[l..r].iter_mut().for_each(|y| *y += x);
+                }
+                2 if l < n => {
+                    assert_eq!(sd.get(l), Sum(naive[l], 1));
+                    sd.set(l, Sum(x, 1));
+                    naive[l] = x;
+                }
+                _ => {
+                    let s = naive[l..r].iter().sum::<i64>();
+                    assert_eq!(sd.prod(l..r), Sum(s, (r - l) as i64));
+                }
+            }
+        }
+    }
+}
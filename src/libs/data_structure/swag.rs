@@ -54,6 +54,116 @@ impl<T: Clone, F: Fn(&T, &T) -> T> Swag<T, F> {
     pub fn is_empty(&self) -> bool {
         self.f_head.is_empty() && self.tail.is_empty()
     }
+
+    pub fn clear(&mut self) {
+        self.f_head.clear();
+        self.tail.clear();
+        self.f_tail = None;
+    }
+}
+
+// the folds of all the windows a[i..i + k]
+pub fn sliding_window_fold<T: Clone, F: Fn(&T, &T) -> T>(a: &[T], k: usize, f: F) -> Vec<T> {
+    assert!(k > 0);
+    let mut swag = Swag::new(f);
+    let mut res = Vec::with_capacity((a.len() + 1).saturating_sub(k));
+    for (i, x) in a.iter().enumerate() {
+        swag.push(x.clone());
+        if i >= k {
+            swag.pop();
+        }
+        if i + 1 >= k {
+            res.push(swag.fold().unwrap());
+        }
+    }
+    res
+}
+
+// each stack keeps (value, fold between the value and the middle) in deque order
+pub struct SwagDeque<T, F> {
+    f: F,
+    front: Vec<(T, T)>,
+    back: Vec<(T, T)>,
+}
+
+impl<T: Clone, F: Fn(&T, &T) -> T> SwagDeque<T, F> {
+    pub fn new(f: F) -> Self {
+        Self {
+            f,
+            front: Vec::new(),
+            back: Vec::new(),
+        }
+    }
+
+    pub fn push_front(&mut self, x: T) {
+        let s = match self.front.last() {
+            Some((_, s)) => (self.f)(&x, s),
+            None => x.clone(),
+        };
+        self.front.push((x, s));
+    }
+
+    pub fn push_back(&mut self, x: T) {
+        let s = match self.back.last() {
+            Some((_, s)) => (self.f)(s, &x),
+            None => x.clone(),
+        };
+        self.back.push((x, s));
+    }
+
+    // moves half of the other stack when one side runs out
+    fn rebalance(&mut self, to_front: bool) {
+        let mut a: Vec<T> = self.front.drain(..).rev().map(|e| e.0).collect();
+        a.extend(self.back.drain(..).map(|e| e.0));
+        let m = if to_front {
+            (a.len() + 1) / 2
+        } else {
+            a.len() / 2
+        };
+        let b = a.split_off(m);
+        for x in a.into_iter().rev() {
+            self.push_front(x);
+        }
+        for x in b {
+            self.push_back(x);
+        }
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.front.is_empty() {
+            self.rebalance(true);
+        }
+        self.front.pop().map(|e| e.0)
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.back.is_empty() {
+            self.rebalance(false);
+        }
+        self.back.pop().map(|e| e.0)
+    }
+
+    pub fn fold(&self) -> Option<T> {
+        match (self.front.last(), self.back.last()) {
+            (Some((_, x)), Some((_, y))) => Some((self.f)(x, y)),
+            (Some((_, x)), None) => Some(x.clone()),
+            (None, Some((_, y))) => Some(y.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.front.len() + self.back.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.front.is_empty() && self.back.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.front.clear();
+        self.back.clear();
+    }
 }
 #[cfg(test)]
 mod tests {
@@ -78,4 +188,49 @@ mod tests {
         assert_eq!(swag.fold(), None);
         swag.pop();
     }
+
+    #[test]
+    fn sliding_window_fold_test() {
+        let a = [4, 2, 5, 1, 3];
+        assert_eq!(
+            sliding_window_fold(&a, 2, |x, y| i32::min(*x, *y)),
+            [2, 2, 1, 1]
+        );
+        assert_eq!(sliding_window_fold(&a, 5, |x, y| x + y), [15]);
+        assert!(sliding_window_fold(&a, 6, |x, y| x + y).is_empty());
+    }
+
+    #[test]
+    fn swag_deque_random() {
+        use crate::random::*;
+        use std::collections::VecDeque;
+        // concatenation checks the order of non-commutative folds
+        let mut rand = Pcg::seed_from_u64(3801);
+        let mut swag = SwagDeque::new(|x: &String, y: &String| x.clone() + y);
+        let mut naive = VecDeque::new();
+        for i in 0..2000 {
+            let x = ((b'a' + (i % 26) as u8) as char).to_string();
+            match rand.next_u32() % 5 {
+                0 => {
+                    swag.push_front(x.clone());
+                    naive.push_front(x);
+                }
+                1 => {
+                    swag.push_back(x.clone());
+                    naive.push_back(x);
+                }
+                2 => assert_eq!(swag.pop_front(), naive.pop_front()),
+                3 => assert_eq!(swag.pop_back(), naive.pop_back()),
+                _ if i % 100 == 0 => {
+                    swag.clear();
+                    naive.clear();
+                }
+                _ => {}
+            }
+            assert_eq!(swag.len(), naive.len());
+            assert_eq!(swag.is_empty(), naive.is_empty());
+            let s: String = naive.iter().map(|s| s.as_str()).collect();
+            assert_eq!(swag.fold(), if s.is_empty() { None } else { Some(s) });
+        }
+    }
 }
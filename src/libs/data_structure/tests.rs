@@ -1,8 +1,8 @@
 use super::*;
 use crate::random::*;
-use fenwick_tree::FenwickTree;
+use fenwick_tree::{FenwickTree, FenwickTree2d, RangeAddFenwickTree};
 use lazy_seg_tree::{LazySegTree, Map};
-use sparse_table::SparseTable;
+use sparse_table::{DisjointSparseTable, SparseTable};
 use wavelet_matrix::WaveletMatrix;
 
 #[test]
@@ -18,6 +18,50 @@ fn sparse_table() {
     }
 }
 
+#[test]
+fn sparse_table_ops() {
+    use sparse_table::{Gcd, Max, Operation, Or};
+    const N: usize = 23;
+    let mut rand = Pcg::seed_from_u64(2801);
+    let a: Vec<_> = (0..N).map(|_| (rand.next_u32() % 60 + 1) as u64).collect();
+    let max: SparseTable<_, Max> = a.iter().copied().collect();
+    let gcd: SparseTable<_, Gcd> = a.iter().copied().collect();
+    let or: SparseTable<_, Or> = a.iter().copied().collect();
+    let argmin: SparseTable<_> = a.iter().copied().zip(0..).collect();
+    for l in 0..N {
+        for r in l + 1..=N {
+            assert_eq!(max.fold(l, r), *a[l..r].iter().max().unwrap());
+            let g = a[l..r].iter().fold(0, |x, &y| Gcd::op(&x, &y));
+            assert_eq!(gcd.fold(l, r), g);
+            assert_eq!(or.fold(l, r), a[l..r].iter().fold(0, |x, y| x | y));
+            let i = (l..r).min_by_key(|&i| a[i]).unwrap();
+            assert_eq!(argmin.min(l, r), &(a[i], i));
+        }
+    }
+}
+
+#[test]
+fn disjoint_sparse_table() {
+    use crate::other::matrix::Matrix;
+    use sparse_table::{Prod, Sum};
+    let mut rand = Pcg::seed_from_u64(2802);
+    for n in 1..20 {
+        let a: Vec<_> = (0..n).map(|_| rand.next_u32() as u64).collect();
+        let sum: DisjointSparseTable<_, Sum> = a.iter().copied().collect();
+        let ms: Vec<_> = (0..n)
+            .map(|_| Matrix::from_elems(2, 2, (0..4).map(|_| (rand.next_u32() % 4) as u64)))
+            .collect();
+        let prod: DisjointSparseTable<_, Prod> = ms.iter().cloned().collect();
+        for l in 0..n {
+            for r in l + 1..=n {
+                assert_eq!(sum.fold(l, r), a[l..r].iter().sum::<u64>());
+                let p = ms[l + 1..r].iter().fold(ms[l].clone(), |x, y| &x * y);
+                assert_eq!(prod.fold(l, r), p);
+            }
+        }
+    }
+}
+
 #[test]
 fn fenwick_tree_add() {
     const N: usize = 20;
@@ -44,6 +88,75 @@ fn fenwick_tree_add() {
     }
 }
 
+#[test]
+fn fenwick_tree_range_sum_lower_bound() {
+    const N: usize = 20;
+    let mut rand = Pcg::seed_from_u64(2701);
+    let mut ft = FenwickTree::new(N, || 0i64, |x, y| x + y);
+    let mut a = [0i64; N];
+    for _ in 0..100 {
+        let i = rand.next_u32() as usize % N;
+        let x = (rand.next_u32() % 10) as i64;
+        ft.add(i, x);
+        a[i] += x;
+        let l = rand.next_u32() as usize % (N + 1);
+        let r = l + rand.next_u32() as usize % (N + 1 - l);
+        let asum: i64 = a[l..r].iter().sum();
+        assert_eq!(ft.range_sum(l..r, |x, y| x - y), asum);
+        let w = (rand.next_u32() % 200) as i64;
+        let lb = (0..N)
+            .find(|&i| a[..=i].iter().sum::<i64>() >= w)
+            .unwrap_or(N);
+        assert_eq!(ft.lower_bound(&w), lb);
+    }
+}
+
+#[test]
+fn range_add_fenwick_tree() {
+    const N: usize = 20;
+    let mut rand = Pcg::seed_from_u64(2702);
+    let mut ft = RangeAddFenwickTree::<i64>::new(N);
+    let mut a = [0i64; N];
+    for i in 0..60 {
+        let l = rand.next_u32() as usize % (N + 1);
+        let r = l + rand.next_u32() as usize % (N + 1 - l);
+        if i % 2 == 0 {
+            let x = (rand.next_u32() % 1000) as i64 - 500;
+            ft.add(l, r, x);
+            for a in &mut a[l..r] {
+                *a += x;
+            }
+        } else {
+            assert_eq!(ft.range_sum(l..r), a[l..r].iter().sum::<i64>());
+        }
+    }
+}
+
+#[test]
+fn fenwick_tree_2d() {
+    const H: usize = 7;
+    const W: usize = 9;
+    let mut rand = Pcg::seed_from_u64(2703);
+    let mut ft = FenwickTree2d::new(H, W, || 0i64, |x, y| x + y);
+    let mut a = vec![vec![0i64; W]; H];
+    for _ in 0..100 {
+        let i = rand.next_u32() as usize % H;
+        let j = rand.next_u32() as usize % W;
+        let x = (rand.next_u32() % 100) as i64;
+        ft.add(i, j, x);
+        a[i][j] += x;
+        let il = rand.next_u32() as usize % (H + 1);
+        let ir = il + rand.next_u32() as usize % (H + 1 - il);
+        let jl = rand.next_u32() as usize % (W + 1);
+        let jr = jl + rand.next_u32() as usize % (W + 1 - jl);
+        let asum: i64 = a[il..ir]
+            .iter()
+            .map(|row| row[jl..jr].iter().sum::<i64>())
+            .sum();
+        assert_eq!(ft.rect_sum(il..ir, jl..jr, |x, y| x - y), asum);
+    }
+}
+
 #[test]
 fn dsu_with_data_drop() {
     use std::{cell::Cell, rc::Rc};
@@ -67,6 +180,94 @@ fn dsu_with_data_drop() {
     assert_eq!(cnt.get(), 0);
 }
 
+#[test]
+fn dsu_variants() {
+    use dsu::{Dsu, FastDsu, RollbackDsu};
+    const N: usize = 30;
+    let mut rand = Pcg::seed_from_u64(3001);
+    let mut dsu = Dsu::new(N);
+    let mut fast = FastDsu::new(N);
+    let mut rb = RollbackDsu::new(N);
+    let mut snapshots = vec![];
+    let mut states = vec![];
+    for _ in 0..40 {
+        let u = rand.next_u32() as usize % N;
+        let v = rand.next_u32() as usize % N;
+        snapshots.push(rb.snapshot());
+        states.push(rb.groups());
+        dsu.unite(u, v);
+        fast.unite(u, v);
+        rb.unite(u, v);
+        assert_eq!(fast.groups(), dsu.groups());
+        assert_eq!(rb.groups(), dsu.groups());
+        for w in 0..N {
+            assert_eq!(fast.size(w), dsu.size(w));
+            assert_eq!(fast.is_same(u, w), dsu.is_same(u, w));
+        }
+    }
+    while let Some(t) = snapshots.pop() {
+        rb.rollback(t);
+        assert_eq!(rb.groups(), states.pop().unwrap());
+    }
+    assert_eq!(rb.groups().len(), N);
+}
+
+#[test]
+fn weighted_dsu() {
+    use dsu::WeightedDsu;
+    const N: usize = 20;
+    let mut rand = Pcg::seed_from_u64(3002);
+    let pot: Vec<i64> = (0..N).map(|_| rand.next_u32() as i64 % 100).collect();
+    let mut wd = WeightedDsu::new(N);
+    let mut naive = dsu::Dsu::new(N);
+    for _ in 0..30 {
+        let u = rand.next_u32() as usize % N;
+        let v = rand.next_u32() as usize % N;
+        assert!(wd.unite(u, v, pot[v] - pot[u]));
+        naive.unite(u, v);
+        if naive.size(u) > 1 {
+            let w = naive.groups().into_iter().find(|g| g.contains(&u)).unwrap();
+            let w = w[rand.next_u32() as usize % w.len()];
+            assert!(!wd.unite(u, w, pot[w] - pot[u] + 1));
+        }
+        for a in 0..N {
+            for b in 0..N {
+                let d = if naive.is_same(a, b) {
+                    Some(pot[b] - pot[a])
+                } else {
+                    None
+                };
+                assert_eq!(wd.diff(a, b), d);
+            }
+        }
+    }
+    assert_eq!(wd.groups(), naive.groups());
+}
+
+#[test]
+fn dsu_with_data_clone_into_groups() {
+    let mut dsu: dsu::DsuWithData<_> = (0..6).map(|i| vec![i]).collect();
+    dsu.unite(0, 3, |x, y| x.extend(y));
+    dsu.unite(4, 3, |x, y| x.extend(y));
+    dsu.unite(1, 5, |x, y| x.extend(y));
+    let cloned = dsu.clone();
+    dsu.unite(1, 2, |x, y| x.extend(y));
+    assert_eq!(format!("{:?}", cloned), "{0: [0, 3, 4], 1: [1, 5], 2: [2]}");
+    let mut groups = cloned.into_groups();
+    for (_, data) in &mut groups {
+        data.sort();
+    }
+    assert_eq!(
+        groups,
+        [
+            (vec![0, 3, 4], vec![0, 3, 4]),
+            (vec![1, 5], vec![1, 5]),
+            (vec![2], vec![2])
+        ]
+    );
+    assert_eq!(dsu[2], [1, 5, 2]);
+}
+
 #[test]
 fn seg_tree_max() {
     impl Monoid for u32 {
@@ -170,3 +371,64 @@ fn wavelet_matrix_rank() {
         assert_eq!(wm.rank(0, N, values[i]), cnt[i]);
     }
 }
+
+#[test]
+fn wavelet_matrix_queries() {
+    const N: usize = 80;
+    let mut rand = Pcg::seed_from_u64(2901);
+    let a: Vec<u64> = (0..N)
+        .map(|_| (rand.next_u32() % 20) as u64 * 1000)
+        .collect();
+    let wm = WaveletMatrix::with_sums(a.clone(), 15);
+    for _ in 0..200 {
+        let l = rand.next_u32() as usize % N;
+        let r = l + rand.next_u32() as usize % (N - l) + 1;
+        let lo = (rand.next_u32() % 21) as u64 * 1000 + rand.next_u32() as u64 % 2;
+        let hi = (rand.next_u32() % 21) as u64 * 1000 + rand.next_u32() as u64 % 2;
+        let mut b = a[l..r].to_vec();
+        b.sort();
+        let freq = b.iter().filter(|&&x| lo <= x && x < hi).count();
+        assert_eq!(wm.range_freq(l, r, lo, hi), freq);
+        assert_eq!(
+            wm.prev_value(l, r, hi),
+            b.iter().rev().find(|&&x| x < hi).copied()
+        );
+        assert_eq!(
+            wm.next_value(l, r, lo),
+            b.iter().find(|&&x| x >= lo).copied()
+        );
+        let k = rand.next_u32() as usize % (r - l + 1);
+        assert_eq!(wm.sum_smallest(l, r, k), b[..k].iter().sum::<u64>());
+        let mut cnt = std::collections::BTreeMap::new();
+        for &x in &b {
+            *cnt.entry(x).or_insert(0) += 1;
+        }
+        let mut top: Vec<_> = cnt.into_iter().collect();
+        top.sort_by_key(|&(x, c)| (std::cmp::Reverse(c), x));
+        top.truncate(3);
+        assert_eq!(wm.top_k(l, r, 3), top);
+    }
+    for x in (0..20).map(|x| x * 1000) {
+        let pos: Vec<_> = (0..N).filter(|&i| a[i] == x).collect();
+        for k in 0..=pos.len() {
+            assert_eq!(wm.select(x, k), pos.get(k).copied());
+        }
+    }
+    assert_eq!(wm.rank(0, N, 1 << 20), 0);
+    assert_eq!(wm.select(1 << 20, 0), None);
+}
+
+#[test]
+fn wavelet_matrix_select_long() {
+    const N: usize = 5000;
+    let mut rand = Pcg::seed_from_u64(2902);
+    let a: Vec<u32> = (0..N).map(|_| rand.next_u32() % 3).collect();
+    let wm = WaveletMatrix::new(a.clone());
+    for x in 0..3 {
+        let pos: Vec<_> = (0..N).filter(|&i| a[i] == x).collect();
+        for (k, &p) in pos.iter().enumerate() {
+            assert_eq!(wm.select(x, k), Some(p));
+        }
+        assert_eq!(wm.select(x, pos.len()), None);
+    }
+}
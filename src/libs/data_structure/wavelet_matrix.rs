@@ -1,18 +1,39 @@
-pub struct WaveletMatrix(Box<[(BitVector, usize)]>);
-impl WaveletMatrix {
-    pub fn new(a: impl Into<Vec<u32>>) -> Self {
-        let mut a = a.into();
+pub use crate::number::Unsigned as Value;
+use std::marker::PhantomData;
+pub struct WaveletMatrix<T = u32> {
+    mat: Box<[(BitVector, usize)]>,
+    sums: Box<[Box<[u64]>]>,
+    len: usize,
+    marker: PhantomData<T>,
+}
+impl<T: Value> WaveletMatrix<T> {
+    pub fn new(a: impl Into<Vec<T>>) -> Self {
+        Self::build(a.into(), T::BITS, false)
+    }
+    // every value must be less than 2^bits
+    pub fn with_bits(a: impl Into<Vec<T>>, bits: usize) -> Self {
+        Self::build(a.into(), bits, false)
+    }
+    // additionally keeps prefix sums per level for `sum_smallest`
+    pub fn with_sums(a: impl Into<Vec<T>>, bits: usize) -> Self {
+        Self::build(a.into(), bits, true)
+    }
+    fn build(a: Vec<T>, bits: usize, sums: bool) -> Self {
+        assert!(bits <= 64);
+        let mut a: Vec<u64> = a.into_iter().map(T::to_u64).collect();
         let n = a.len();
+        assert!(bits == 64 || a.iter().all(|x| x >> bits == 0));
         let mut a_tmp = Vec::with_capacity(n);
-        let mut mat = Vec::with_capacity(32);
-        for b in (0..32).rev() {
+        let mut mat = Vec::with_capacity(bits);
+        let mut sum_tab = Vec::new();
+        for b in (0..bits).rev() {
             let mut bits = vec![0; n / 64 + 1];
             let mut cnt_ones = 0;
             for (i, x) in a.iter().enumerate() {
-                bits[i / 64] |= ((x >> b & 1) as u64) << i % 64;
+                bits[i / 64] |= (x >> b & 1) << i % 64;
                 cnt_ones += x >> b & 1;
             }
-            mat.push((BitVector::new(bits), n - cnt_ones as usize));
+            mat.push((BitVector::new(bits, n), n - cnt_ones as usize));
             a_tmp.splice(
                 ..,
                 a.iter()
@@ -21,57 +42,221 @@ impl WaveletMatrix {
                     .copied(),
             );
             std::mem::swap(&mut a, &mut a_tmp);
+            if sums {
+                sum_tab.push(
+                    std::iter::once(0)
+                        .chain(a.iter().scan(0u64, |s, &x| {
+                            *s = s.wrapping_add(x);
+                            Some(*s)
+                        }))
+                        .collect(),
+                );
+            }
         }
         mat.reverse();
-        Self(mat.into())
-    }
-    pub fn rank(&self, mut l: usize, mut r: usize, x: u32) -> usize {
-        for d in (0..32).rev() {
-            let (bv, cnt_zeros) = &self.0[d];
-            if x >> d & 1 == 0 {
-                l = bv.rank_zero(l);
-                r = bv.rank_zero(r);
-            } else {
-                l = cnt_zeros + bv.rank_one(l);
-                r = cnt_zeros + bv.rank_one(r);
-            }
+        sum_tab.reverse();
+        Self {
+            mat: mat.into(),
+            sums: sum_tab.into(),
+            len: n,
+            marker: PhantomData,
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    fn height(&self) -> usize {
+        self.mat.len()
+    }
+    fn fits(&self, x: u64) -> bool {
+        self.height() == 64 || x >> self.height() == 0
+    }
+    fn down(&self, d: usize, l: usize, r: usize, f: u64) -> (usize, usize) {
+        let (bv, cnt_zeros) = &self.mat[d];
+        if f == 0 {
+            (bv.rank_zero(l), bv.rank_zero(r))
+        } else {
+            (cnt_zeros + bv.rank_one(l), cnt_zeros + bv.rank_one(r))
+        }
+    }
+    pub fn rank(&self, mut l: usize, mut r: usize, x: T) -> usize {
+        let x = x.to_u64();
+        if !self.fits(x) {
+            return 0;
+        }
+        for d in (0..self.height()).rev() {
+            let lr = self.down(d, l, r, x >> d & 1);
+            l = lr.0;
+            r = lr.1;
         }
         r - l
     }
-    pub fn quantile(&self, mut l: usize, mut r: usize, mut k: usize) -> u32 {
+    pub fn quantile(&self, mut l: usize, mut r: usize, mut k: usize) -> T {
+        assert!(k < r - l);
         let mut x = 0;
-        for d in (0..32).rev() {
-            let (bv, cnt_zeros) = &self.0[d];
-            let l0 = bv.rank_zero(l);
-            let r0 = bv.rank_zero(r);
+        for d in (0..self.height()).rev() {
+            let (l0, r0) = self.down(d, l, r, 0);
             if k < r0 - l0 {
                 l = l0;
                 r = r0;
             } else {
                 x |= 1 << d;
                 k -= r0 - l0;
-                l = cnt_zeros + bv.rank_one(l);
-                r = cnt_zeros + bv.rank_one(r);
+                let lr = self.down(d, l, r, 1);
+                l = lr.0;
+                r = lr.1;
+            }
+        }
+        T::from_u64(x)
+    }
+    // the number of values less than x in [l, r)
+    pub fn count_less(&self, mut l: usize, mut r: usize, x: T) -> usize {
+        let x = x.to_u64();
+        if !self.fits(x) {
+            return r - l;
+        }
+        let mut res = 0;
+        for d in (0..self.height()).rev() {
+            let f = x >> d & 1;
+            if f == 1 {
+                let (l0, r0) = self.down(d, l, r, 0);
+                res += r0 - l0;
             }
+            let lr = self.down(d, l, r, f);
+            l = lr.0;
+            r = lr.1;
+        }
+        res
+    }
+    // the number of values in [lo, hi) in [l, r)
+    pub fn range_freq(&self, l: usize, r: usize, lo: T, hi: T) -> usize {
+        if lo >= hi {
+            return 0;
         }
-        x
+        self.count_less(l, r, hi) - self.count_less(l, r, lo)
+    }
+    // the maximum value less than upper in [l, r)
+    pub fn prev_value(&self, l: usize, r: usize, upper: T) -> Option<T> {
+        let c = self.count_less(l, r, upper);
+        if c == 0 {
+            None
+        } else {
+            Some(self.quantile(l, r, c - 1))
+        }
+    }
+    // the minimum value not less than lower in [l, r)
+    pub fn next_value(&self, l: usize, r: usize, lower: T) -> Option<T> {
+        let c = self.count_less(l, r, lower);
+        if c == r - l {
+            None
+        } else {
+            Some(self.quantile(l, r, c))
+        }
+    }
+    // the position of the k-th (0-indexed) occurrence of x
+    pub fn select(&self, x: T, k: usize) -> Option<usize> {
+        let x = x.to_u64();
+        if !self.fits(x) {
+            return None;
+        }
+        let (mut l, mut r) = (0, self.len);
+        for d in (0..self.height()).rev() {
+            let lr = self.down(d, l, r, x >> d & 1);
+            l = lr.0;
+            r = lr.1;
+        }
+        if k >= r - l {
+            return None;
+        }
+        let mut p = l + k;
+        for d in 0..self.height() {
+            let (bv, cnt_zeros) = &self.mat[d];
+            p = if x >> d & 1 == 0 {
+                bv.select_zero(p)
+            } else {
+                bv.select_one(p - cnt_zeros)
+            };
+        }
+        Some(p)
+    }
+    // the k most frequent values in [l, r) with their counts, by count descending then value
+    pub fn top_k(&self, l: usize, r: usize, k: usize) -> Vec<(T, usize)> {
+        use std::{cmp::Reverse, collections::BinaryHeap};
+        let mut res = Vec::with_capacity(k);
+        let mut que = BinaryHeap::new();
+        if l < r {
+            que.push((r - l, Reverse(0u64), self.height(), l));
+        }
+        while let Some((w, Reverse(x), d, l)) = que.pop() {
+            if res.len() >= k {
+                break;
+            }
+            if d == 0 {
+                res.push((T::from_u64(x), w));
+                continue;
+            }
+            let r = l + w;
+            for f in 0..2 {
+                let (l, r) = self.down(d - 1, l, r, f);
+                if l < r {
+                    que.push((r - l, Reverse(x | f << (d - 1)), d - 1, l));
+                }
+            }
+        }
+        res
+    }
+    // the sum of the k smallest values in [l, r), modulo 2^64; requires `with_sums`
+    pub fn sum_smallest(&self, mut l: usize, mut r: usize, mut k: usize) -> u64 {
+        assert!(k <= r - l);
+        assert!(self.height() == 0 || !self.sums.is_empty());
+        let mut res = 0u64;
+        let mut x = 0u64;
+        for d in (0..self.height()).rev() {
+            let (l0, r0) = self.down(d, l, r, 0);
+            if k < r0 - l0 {
+                l = l0;
+                r = r0;
+            } else {
+                let s = &self.sums[d];
+                res = res.wrapping_add(s[r0].wrapping_sub(s[l0]));
+                x |= 1 << d;
+                k -= r0 - l0;
+                let lr = self.down(d, l, r, 1);
+                l = lr.0;
+                r = lr.1;
+            }
+        }
+        res.wrapping_add(x.wrapping_mul(k as u64))
     }
 }
+const SAMPLE: usize = 512;
 struct BitVector {
     sum: Vec<u32>,
     bits: Vec<u64>,
+    // positions of every SAMPLE-th one / zero
+    sel: [Vec<usize>; 2],
 }
 impl BitVector {
-    fn new(bits: Vec<u64>) -> Self {
-        Self {
-            sum: std::iter::once(0)
-                .chain(bits.iter().map(|x| x.count_ones()).scan(0u32, |s, x| {
-                    *s += x;
-                    Some(*s)
-                }))
-                .collect(),
-            bits,
+    fn new(bits: Vec<u64>, n: usize) -> Self {
+        let sum: Vec<_> = std::iter::once(0)
+            .chain(bits.iter().map(|x| x.count_ones()).scan(0u32, |s, x| {
+                *s += x;
+                Some(*s)
+            }))
+            .collect();
+        let mut sel = [Vec::new(), Vec::new()];
+        let mut cnt = [0; 2];
+        for i in 0..n {
+            let f = (bits[i / 64] >> i % 64 & 1) as usize;
+            if cnt[f] % SAMPLE == 0 {
+                sel[f].push(i);
+            }
+            cnt[f] += 1;
         }
+        Self { sum, bits, sel }
     }
     fn rank_one(&self, r: usize) -> usize {
         (self.sum[r / 64] + (self.bits[r / 64] & (1 << r % 64) - 1).count_ones()) as usize
@@ -79,19 +264,40 @@ impl BitVector {
     fn rank_zero(&self, r: usize) -> usize {
         r - self.rank_one(r)
     }
-    /*
-    fn select_one(&self, n: usize) -> usize {
-        let mut l = -1;
-        let mut r = 64 * self.bits.len() as isize;
+    fn rank(&self, f: usize, w: usize) -> usize {
+        let ones = self.sum[w] as usize;
+        if f == 1 {
+            ones
+        } else {
+            64 * w - ones
+        }
+    }
+    fn select(&self, f: usize, k: usize) -> usize {
+        let sel = &self.sel[f];
+        let mut l = sel[k / SAMPLE] / 64;
+        let mut r = sel
+            .get(k / SAMPLE + 1)
+            .map(|p| p / 64 + 1)
+            .unwrap_or(self.bits.len());
         while r - l > 1 {
             let h = (l + r) / 2;
-            if self.rank_one(h as usize) >= n {
-                r = h;
-            } else {
+            if self.rank(f, h) <= k {
                 l = h;
+            } else {
+                r = h;
             }
         }
-        r as usize
+        let mut word = if f == 1 { self.bits[l] } else { !self.bits[l] };
+        for _ in 0..k - self.rank(f, l) {
+            word &= word - 1;
+        }
+        64 * l + word.trailing_zeros() as usize
+    }
+    // the position of the k-th (0-indexed) one
+    fn select_one(&self, k: usize) -> usize {
+        self.select(1, k)
+    }
+    fn select_zero(&self, k: usize) -> usize {
+        self.select(0, k)
     }
-    */
 }
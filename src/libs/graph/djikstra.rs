@@ -1,8 +1,45 @@
-use super::LabeledGraph;
+use super::shortest_path::ShortestPaths;
+use crate::data_structure::heap::RadixHeap;
+use crate::graph2::Graph;
+use crate::number::Unsigned;
+use std::ops::Add;
 
-pub fn djikstra<T>(g: &LabeledGraph<T>, s: usize) -> Vec<Option<T>>
+pub fn djikstra<'a, T, G>(g: &'a G, s: usize) -> Vec<Option<T>>
 where
-    T: std::ops::Add<Output = T> + Ord + Default + Copy,
+    G: Graph<'a, T> + ?Sized,
+    T: 'a + Add<Output = T> + Ord + Default + Copy,
+{
+    djikstra_multi(g, Some(s)).dist
+}
+
+pub fn djikstra_with_parents<'a, T, G>(g: &'a G, s: usize) -> ShortestPaths<T>
+where
+    G: Graph<'a, T> + ?Sized,
+    T: 'a + Add<Output = T> + Ord + Default + Copy,
+{
+    djikstra_multi(g, Some(s))
+}
+
+// the distances from the nearest source
+pub fn djikstra_multi<'a, T, G, I>(g: &'a G, sources: I) -> ShortestPaths<T>
+where
+    G: Graph<'a, T> + ?Sized,
+    T: 'a + Add<Output = T> + Ord + Default + Copy,
+    I: IntoIterator<Item = usize>,
+{
+    djikstra_implicit(g.len(), sources, |u, f| {
+        for (v, &w) in g.adj(u) {
+            f(v, w);
+        }
+    })
+}
+
+// neighbors(u, f) calls f(v, w) for each edge from u to v of weight w
+pub fn djikstra_implicit<T, I, F>(n: usize, sources: I, mut neighbors: F) -> ShortestPaths<T>
+where
+    T: Add<Output = T> + Ord + Default + Copy,
+    I: IntoIterator<Item = usize>,
+    F: FnMut(usize, &mut dyn FnMut(usize, T)),
 {
     use std::{cmp::Ordering, collections::BinaryHeap};
     struct Node<T>(usize, T);
@@ -22,23 +59,118 @@ where
             other.1.cmp(&self.1)
         }
     }
-    let mut dist = vec![None; g.len()];
-    dist[s] = Some(T::default());
+    let mut res = ShortestPaths::new(n);
     let mut que = BinaryHeap::new();
-    que.push(Node(s, T::default()));
+    for s in sources {
+        res.dist[s] = Some(T::default());
+        que.push(Node(s, T::default()));
+    }
     while let Some(Node(u, d)) = que.pop() {
-        if matches!(dist[u], Some(du) if d > du) {
+        if matches!(res.dist[u], Some(du) if d > du) {
             continue;
         }
-        for &(v, w) in &g[u] {
+        neighbors(u, &mut |v, w| {
             let dsuv = d + w;
-            if dist[v].map(|dv| dsuv < dv).unwrap_or(true) {
-                dist[v] = Some(dsuv);
+            if res.dist[v].map(|dv| dsuv < dv).unwrap_or(true) {
+                res.dist[v] = Some(dsuv);
+                res.par[v] = u;
                 que.push(Node(v, dsuv));
             }
+        });
+    }
+    res
+}
+
+// O(n^2 + m) without a heap, for dense graphs
+pub fn djikstra_dense<T, I, F>(n: usize, sources: I, mut neighbors: F) -> ShortestPaths<T>
+where
+    T: Add<Output = T> + Ord + Default + Copy,
+    I: IntoIterator<Item = usize>,
+    F: FnMut(usize, &mut dyn FnMut(usize, T)),
+{
+    let mut res = ShortestPaths::new(n);
+    for s in sources {
+        res.dist[s] = Some(T::default());
+    }
+    let mut done = vec![false; n];
+    loop {
+        let mut next = None;
+        for (u, &du) in res.dist.iter().enumerate() {
+            if let (false, Some(du)) = (done[u], du) {
+                if next.map(|(_, d)| du < d).unwrap_or(true) {
+                    next = Some((u, du));
+                }
+            }
+        }
+        let (u, d) = match next {
+            Some(x) => x,
+            None => break,
+        };
+        done[u] = true;
+        neighbors(u, &mut |v, w| {
+            let dsuv = d + w;
+            if res.dist[v].map(|dv| dsuv < dv).unwrap_or(true) {
+                res.dist[v] = Some(dsuv);
+                res.par[v] = u;
+            }
+        });
+    }
+    res
+}
+
+// with a radix heap; the distances must fit in u64
+pub fn djikstra_radix<T, I, F>(n: usize, sources: I, mut neighbors: F) -> ShortestPaths<T>
+where
+    T: Unsigned + Add<Output = T> + Default,
+    I: IntoIterator<Item = usize>,
+    F: FnMut(usize, &mut dyn FnMut(usize, T)),
+{
+    let mut res = ShortestPaths::new(n);
+    let mut que = RadixHeap::new();
+    for s in sources {
+        res.dist[s] = Some(T::default());
+        que.push(T::default().to_u64(), s);
+    }
+    while let Some((d, u)) = que.pop() {
+        let d = T::from_u64(d);
+        if matches!(res.dist[u], Some(du) if d > du) {
+            continue;
         }
+        neighbors(u, &mut |v, w| {
+            let dsuv = d + w;
+            if res.dist[v].map(|dv| dsuv < dv).unwrap_or(true) {
+                res.dist[v] = Some(dsuv);
+                res.par[v] = u;
+                que.push(dsuv.to_u64(), v);
+            }
+        });
+    }
+    res
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Strategy {
+    BinaryHeap,
+    RadixHeap,
+    // O(n^2 + m)
+    Dense,
+}
+pub fn djikstra_by<T, I, F>(
+    n: usize,
+    sources: I,
+    strategy: Strategy,
+    neighbors: F,
+) -> ShortestPaths<T>
+where
+    T: Unsigned + Add<Output = T> + Default,
+    I: IntoIterator<Item = usize>,
+    F: FnMut(usize, &mut dyn FnMut(usize, T)),
+{
+    match strategy {
+        Strategy::BinaryHeap => djikstra_implicit(n, sources, neighbors),
+        Strategy::RadixHeap => djikstra_radix(n, sources, neighbors),
+        Strategy::Dense => djikstra_dense(n, sources, neighbors),
     }
-    dist
 }
 
 #[cfg(test)]
@@ -85,4 +217,74 @@ mod tests {
             assert_eq!(dist_d, dist_bf);
         }
     }
+
+    #[test]
+    fn djikstra_strategies() {
+        use super::*;
+        let mut rand = Pcg::seed_from_u64(4701);
+        for _ in 0..20 {
+            let n = rand.next_u32() as usize % 30 + 1;
+            let mut g = LabeledGraph::builder(n);
+            for _ in 0..rand.next_u32() % 200 {
+                let u = rand.next_u32() as usize % n;
+                let v = rand.next_u32() as usize % n;
+                g.edge(u, v, rand.next_u64() % 1000);
+            }
+            let g = g.build();
+            let s = rand.next_u32() as usize % n;
+            let expected = super::djikstra(&g, s);
+            for strategy in [Strategy::BinaryHeap, Strategy::RadixHeap, Strategy::Dense] {
+                let sp = djikstra_by(n, Some(s), strategy, |u, f| {
+                    for &(v, w) in &g[u] {
+                        f(v, w);
+                    }
+                });
+                assert_eq!(sp.dist, expected);
+                assert_eq!(
+                    sp.dist_or(!0),
+                    expected.iter().map(|d| d.unwrap_or(!0)).collect::<Vec<_>>()
+                );
+                for (t, &e) in expected.iter().enumerate() {
+                    if let Some(path) = sp.path_to(t) {
+                        let w = path
+                            .windows(2)
+                            .map(|p| {
+                                g[p[0]]
+                                    .iter()
+                                    .filter(|e| e.0 == p[1])
+                                    .map(|e| e.1)
+                                    .min()
+                                    .unwrap()
+                            })
+                            .sum::<u64>();
+                        assert_eq!(Some(w), e);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn djikstra_grid() {
+        use super::*;
+        // entering a cell costs its digit; '#' is a wall
+        let grid = ["1911", "1#91", "1#11", "1119"];
+        let (h, w) = (4, 4);
+        let cell = |i: usize| grid[i / w].as_bytes()[i % w];
+        let neighbors = |u: usize, f: &mut dyn FnMut(usize, u32)| {
+            let (i, j) = (u / w, u % w);
+            for (di, dj) in [(0, 1), (1, 0), (0, !0), (!0, 0)] {
+                let (ni, nj) = (i.wrapping_add(di), j.wrapping_add(dj));
+                if ni < h && nj < w && cell(ni * w + nj) != b'#' {
+                    f(ni * w + nj, (cell(ni * w + nj) - b'0') as u32);
+                }
+            }
+        };
+        for strategy in [Strategy::BinaryHeap, Strategy::RadixHeap, Strategy::Dense] {
+            let sp = djikstra_by(h * w, Some(0), strategy, neighbors);
+            assert_eq!(sp.dist[h * w - 1], Some(14));
+            assert_eq!(sp.dist[5], None);
+            assert_eq!(sp.path_to(11), Some(vec![0, 4, 8, 12, 13, 14, 10, 11]));
+        }
+    }
 }
@@ -0,0 +1,118 @@
+use crate::data_structure::dsu::RollbackDsu;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    Add(usize, usize),
+    Remove(usize, usize),
+    Query(usize, usize),
+}
+
+// answers the `Query` events in order; edges are undirected and may be parallel
+pub fn dynamic_connectivity(n: usize, events: &[Event]) -> Vec<bool> {
+    let q = events.len();
+    let size = q.next_power_of_two();
+    let mut seg = vec![Vec::new(); 2 * size];
+    let mut alive = std::collections::HashMap::new();
+    let mut add_range = |mut l: usize, mut r: usize, e: (usize, usize)| {
+        l += size;
+        r += size;
+        while l < r {
+            if l % 2 == 1 {
+                seg[l].push(e);
+                l += 1;
+            }
+            if r % 2 == 1 {
+                r -= 1;
+                seg[r].push(e);
+            }
+            l /= 2;
+            r /= 2;
+        }
+    };
+    for (t, e) in events.iter().enumerate() {
+        match *e {
+            Event::Add(u, v) => alive
+                .entry((u.min(v), u.max(v)))
+                .or_insert_with(Vec::new)
+                .push(t),
+            Event::Remove(u, v) => {
+                let s = alive
+                    .get_mut(&(u.min(v), u.max(v)))
+                    .and_then(Vec::pop)
+                    .expect("removed edge does not exist");
+                add_range(s, t, (u, v));
+            }
+            Event::Query(..) => {}
+        }
+    }
+    for (e, ss) in alive {
+        for s in ss {
+            add_range(s, q, e);
+        }
+    }
+    let mut dsu = RollbackDsu::new(n);
+    let mut snapshot = vec![0; 2 * size];
+    let mut res = Vec::new();
+    let mut stack = vec![1];
+    while let Some(i) = stack.pop() {
+        if i as isize >= 0 {
+            snapshot[i] = dsu.snapshot();
+            for &(u, v) in &seg[i] {
+                dsu.unite(u, v);
+            }
+            if i >= size {
+                if let Some(&Event::Query(u, v)) = events.get(i - size) {
+                    res.push(dsu.is_same(u, v));
+                }
+                dsu.rollback(snapshot[i]);
+            } else {
+                stack.push(!i);
+                stack.push(2 * i + 1);
+                stack.push(2 * i);
+            }
+        } else {
+            dsu.rollback(snapshot[!i]);
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{data_structure::dsu::Dsu, random::*};
+
+    #[test]
+    fn dynamic_connectivity_random() {
+        const N: usize = 8;
+        let mut rand = Pcg::seed_from_u64(3101);
+        for _ in 0..20 {
+            let mut edges = vec![];
+            let mut events = vec![];
+            let mut naive = vec![];
+            for _ in 0..100 {
+                let u = rand.next_u32() as usize % N;
+                let v = rand.next_u32() as usize % N;
+                match rand.next_u32() % 3 {
+                    0 => {
+                        edges.push((u, v));
+                        events.push(Event::Add(u, v));
+                    }
+                    1 if !edges.is_empty() => {
+                        let (u, v) = edges.swap_remove(rand.next_u32() as usize % edges.len());
+                        events.push(Event::Remove(v, u));
+                    }
+                    _ => {
+                        let mut dsu = Dsu::new(N);
+                        for &(a, b) in &edges {
+                            dsu.unite(a, b);
+                        }
+                        naive.push(dsu.is_same(u, v));
+                        events.push(Event::Query(u, v));
+                    }
+                }
+            }
+            assert_eq!(dynamic_connectivity(N, &events), naive);
+        }
+    }
+}
@@ -1,44 +1,96 @@
+use crate::data_structure::{
+    lazy_seg_tree::{LazySegTree, Map},
+    Monoid, SegmentTree,
+};
+use crate::graph2;
+use std::ops::Range;
+
+#[deprecated(note = "Hld::new takes any graph2::Graph")]
+pub type Graph = [Vec<usize>];
+
 #[derive(Debug)]
 pub struct Hld {
+    // indexed by the position in the decomposition
     head: Vec<usize>,
     par: Vec<usize>,
+    depth: Vec<usize>,
+    size: Vec<usize>,
+    vs: Vec<usize>,
+    // the position of each vertex
     tab: Vec<usize>,
 }
-pub type Graph = [Vec<usize>];
 impl Hld {
-    pub fn new(g: &Graph, root: usize) -> Self {
-        let mut heavy = vec![!0; g.len()];
-        Self::dfs_heavy(g, &mut heavy, &mut vec![1; g.len()], root, !0);
+    pub fn new<'a, T: 'a, G: graph2::Graph<'a, T> + ?Sized>(g: &'a G, root: usize) -> Self {
+        let n = g.len();
+        let mut par = vec![!0; n];
+        let mut depth = vec![0; n];
+        let mut ord = Vec::with_capacity(n);
+        let mut stk = vec![root];
+        while let Some(u) = stk.pop() {
+            ord.push(u);
+            let p = par[u];
+            for v in g.adj_unlabeled(u).filter(|&v| v != p) {
+                par[v] = u;
+                depth[v] = depth[u] + 1;
+                stk.push(v);
+            }
+        }
+        let mut heavy = vec![!0; n];
+        let mut size = vec![1; n];
+        for &u in ord.iter().rev() {
+            let mut max = 0;
+            for v in g.adj_unlabeled(u).filter(|&v| v != par[u]) {
+                if size[v] > max {
+                    max = size[v];
+                    heavy[u] = v;
+                }
+                size[u] += size[v];
+            }
+        }
         let mut hld = Hld {
-            head: Vec::with_capacity(g.len()),
-            par: Vec::with_capacity(g.len()),
-            tab: vec![0; g.len()],
+            head: Vec::with_capacity(n),
+            par: Vec::with_capacity(n),
+            depth: Vec::with_capacity(n),
+            size: Vec::with_capacity(n),
+            vs: Vec::with_capacity(n),
+            tab: vec![!0; n],
         };
-        hld.dfs_build(g, &heavy, root, !0, root);
-        hld
-    }
-    fn dfs_heavy(g: &Graph, heavy: &mut [usize], size: &mut [usize], u: usize, p: usize) {
-        let mut max = 0;
-        for &v in g[u].iter().filter(|&&v| v != p) {
-            Self::dfs_heavy(g, heavy, size, v, u);
-            if size[v] > max {
-                max = size[v];
-                heavy[u] = v;
+        // the heavy child is visited right after its parent
+        let mut stk = vec![(root, root)];
+        while let Some((u, h)) = stk.pop() {
+            hld.tab[u] = hld.head.len();
+            hld.head.push(h);
+            hld.par.push(par[u]);
+            hld.depth.push(depth[u]);
+            hld.size.push(size[u]);
+            hld.vs.push(u);
+            let k = stk.len();
+            stk.extend(
+                g.adj_unlabeled(u)
+                    .filter(|&v| v != par[u] && v != heavy[u])
+                    .map(|v| (v, v)),
+            );
+            stk[k..].reverse();
+            if heavy[u] != !0 {
+                stk.push((heavy[u], h));
             }
-            size[u] += size[v];
         }
+        hld
     }
-    fn dfs_build(&mut self, g: &Graph, heavy: &[usize], u: usize, p: usize, h: usize) {
-        self.tab[u] = self.head.len();
-        self.head.push(h);
-        self.par.push(p);
-        if heavy[u] == !0 {
-            return;
-        }
-        self.dfs_build(g, heavy, heavy[u], u, h);
-        for &v in g[u].iter().filter(|&&v| v != p && v != heavy[u]) {
-            self.dfs_build(g, heavy, v, u, v);
-        }
+    // the position of v; the vertices reachable from root get 0..count
+    pub fn index(&self, v: usize) -> usize {
+        self.tab[v]
+    }
+    pub fn parent(&self, v: usize) -> Option<usize> {
+        Some(self.par[self.tab[v]]).filter(|&p| p != !0)
+    }
+    pub fn depth(&self, v: usize) -> usize {
+        self.depth[self.tab[v]]
+    }
+    // the positions of the subtree of v
+    pub fn subtree_range(&self, v: usize) -> Range<usize> {
+        let i = self.tab[v];
+        i..i + self.size[i]
     }
     pub fn lca(&self, mut u: usize, mut v: usize) -> usize {
         loop {
@@ -51,4 +103,435 @@ impl Hld {
             v = self.par[self.tab[self.head[self.tab[v]]]];
         }
     }
+    pub fn dist(&self, u: usize, v: usize) -> usize {
+        self.depth(u) + self.depth(v) - 2 * self.depth(self.lca(u, v))
+    }
+    // the k-th ancestor of v
+    pub fn la(&self, mut v: usize, mut k: usize) -> Option<usize> {
+        if k > self.depth(v) {
+            return None;
+        }
+        loop {
+            let i = self.tab[v];
+            let h = self.tab[self.head[i]];
+            if i - h >= k {
+                return Some(self.vs[i - k]);
+            }
+            k -= i - h + 1;
+            v = self.par[h];
+        }
+    }
+    // the ranges of positions along the path from u to v, each with whether it is
+    // traversed from right to left; with edge, each vertex stands for the edge to its
+    // parent and the lca is excluded
+    pub fn path_ranges(&self, u: usize, v: usize, edge: bool) -> Vec<(Range<usize>, bool)> {
+        let (mut i, mut j) = (self.tab[u], self.tab[v]);
+        let mut up = vec![];
+        let mut down = vec![];
+        while self.head[i] != self.head[j] {
+            let (hi, hj) = (self.tab[self.head[i]], self.tab[self.head[j]]);
+            if hi > hj {
+                up.push((hi..i + 1, true));
+                i = self.tab[self.par[hi]];
+            } else {
+                down.push((hj..j + 1, false));
+                j = self.tab[self.par[hj]];
+            }
+        }
+        let e = edge as usize;
+        if i > j {
+            up.push((j + e..i + 1, true));
+        } else if i + e <= j {
+            down.push((i + e..j + 1, false));
+        }
+        up.extend(down.into_iter().rev());
+        up.retain(|r| !r.0.is_empty());
+        up
+    }
+}
+
+// the product in both directions
+struct Both<T>(T, T);
+impl<T: Monoid> Monoid for Both<T> {
+    fn id() -> Self {
+        Both(T::id(), T::id())
+    }
+    fn op(&self, other: &Self) -> Self {
+        Both(self.0.op(&other.0), other.1.op(&self.1))
+    }
+}
+struct BothMap<F>(F);
+impl<F: Monoid> Monoid for BothMap<F> {
+    fn id() -> Self {
+        BothMap(F::id())
+    }
+    fn op(&self, other: &Self) -> Self {
+        BothMap(self.0.op(&other.0))
+    }
+}
+impl<T, F: Map<T>> Map<Both<T>> for BothMap<F> {
+    fn map(&self, x: Both<T>) -> Both<T> {
+        Both(self.0.map(x.0), self.0.map(x.1))
+    }
+}
+fn path_prod<T: Monoid, P: FnMut(usize, usize) -> Both<T>>(
+    hld: &Hld,
+    u: usize,
+    v: usize,
+    edge: bool,
+    mut prod: P,
+) -> T {
+    let mut res = T::id();
+    for (r, rev) in hld.path_ranges(u, v, edge) {
+        let x = prod(r.start, r.end);
+        res = res.op(if rev { &x.1 } else { &x.0 });
+    }
+    res
+}
+
+// path products on a tree; with edge, the value of each edge is kept at its child
+pub struct HldSegTree<T> {
+    hld: Hld,
+    seg: SegmentTree<Both<T>>,
+    edge: bool,
+}
+impl<T: Monoid + Clone> HldSegTree<T> {
+    // a is indexed by vertex
+    pub fn new(hld: Hld, a: Vec<T>, edge: bool) -> Self {
+        let mut b: Vec<_> = a.into_iter().map(Some).collect();
+        let seg = hld
+            .vs
+            .iter()
+            .map(|&v| {
+                let x = b[v].take().unwrap();
+                Both(x.clone(), x)
+            })
+            .collect();
+        Self { hld, seg, edge }
+    }
+    pub fn hld(&self) -> &Hld {
+        &self.hld
+    }
+    pub fn set(&mut self, v: usize, x: T) {
+        self.seg.set(self.hld.index(v), Both(x.clone(), x));
+    }
+    pub fn get(&self, v: usize) -> &T {
+        &self.seg[self.hld.index(v)].0
+    }
+    // the product along the path from u to v
+    pub fn prod_path(&self, u: usize, v: usize) -> T {
+        path_prod(&self.hld, u, v, self.edge, |l, r| self.seg.prod(l, r))
+    }
+    pub fn prod_subtree(&self, v: usize) -> T {
+        let r = self.hld.subtree_range(v);
+        self.seg.prod(r.start + self.edge as usize, r.end).0
+    }
+}
+
+pub struct HldLazySegTree<T, F> {
+    hld: Hld,
+    seg: LazySegTree<Both<T>, BothMap<F>>,
+    edge: bool,
+}
+impl<T: Monoid + Clone, F: Monoid + Map<T>> HldLazySegTree<T, F> {
+    // a is indexed by vertex
+    pub fn new(hld: Hld, a: Vec<T>, edge: bool) -> Self {
+        let mut b: Vec<_> = a.into_iter().map(Some).collect();
+        let seg = hld
+            .vs
+            .iter()
+            .map(|&v| {
+                let x = b[v].take().unwrap();
+                Both(x.clone(), x)
+            })
+            .collect();
+        Self { hld, seg, edge }
+    }
+    pub fn hld(&self) -> &Hld {
+        &self.hld
+    }
+    pub fn set(&mut self, v: usize, x: T) {
+        self.seg.set(self.hld.index(v), Both(x.clone(), x));
+    }
+    pub fn get(&mut self, v: usize) -> T {
+        let i = self.hld.index(v);
+        self.seg.prod(i, i + 1).0
+    }
+    pub fn prod_path(&mut self, u: usize, v: usize) -> T {
+        let seg = &mut self.seg;
+        path_prod(&self.hld, u, v, self.edge, |l, r| seg.prod(l, r))
+    }
+    pub fn prod_subtree(&mut self, v: usize) -> T {
+        let r = self.hld.subtree_range(v);
+        self.seg.prod(r.start + self.edge as usize, r.end).0
+    }
+    pub fn apply_path(&mut self, u: usize, v: usize, f: F) {
+        let f = BothMap(f);
+        for (r, _) in self.hld.path_ranges(u, v, self.edge) {
+            self.seg.apply(r.start, r.end, &f);
+        }
+    }
+    pub fn apply_subtree(&mut self, v: usize, f: F) {
+        let r = self.hld.subtree_range(v);
+        self.seg
+            .apply(r.start + self.edge as usize, r.end, &BothMap(f));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::*;
+
+    #[test]
+    fn hld_lca_random() {
+        let mut rand = Pcg::seed_from_u64(4203);
+        for _ in 0..20 {
+            let n = rand.next_u32() as usize % 50 + 1;
+            let par: Vec<_> = (0..n)
+                .map(|v| {
+                    if v == 0 {
+                        !0
+                    } else {
+                        rand.next_u32() as usize % v
+                    }
+                })
+                .collect();
+            let mut g = vec![vec![]; n];
+            for v in 1..n {
+                g[v].push(par[v]);
+                g[par[v]].push(v);
+            }
+            let hld = Hld::new(&g, 0);
+            let depth = |mut v: usize| {
+                let mut d = 0;
+                while v != 0 {
+                    v = par[v];
+                    d += 1;
+                }
+                d
+            };
+            for _ in 0..50 {
+                let mut u = rand.next_u32() as usize % n;
+                let mut v = rand.next_u32() as usize % n;
+                let w = hld.lca(u, v);
+                while u != v {
+                    if depth(u) < depth(v) {
+                        std::mem::swap(&mut u, &mut v);
+                    }
+                    u = par[u];
+                }
+                assert_eq!(w, u);
+            }
+        }
+    }
+
+    fn random_tree(rand: &mut Pcg, n: usize) -> (Vec<usize>, Vec<Vec<usize>>) {
+        let par: Vec<_> = (0..n)
+            .map(|v| {
+                if v == 0 {
+                    !0
+                } else {
+                    rand.next_u32() as usize % v
+                }
+            })
+            .collect();
+        let mut g = vec![vec![]; n];
+        for v in 1..n {
+            g[v].push(par[v]);
+            g[par[v]].push(v);
+        }
+        (par, g)
+    }
+    // the vertices along the path from u to v
+    fn naive_path(par: &[usize], mut u: usize, mut v: usize) -> Vec<usize> {
+        let depth = |mut v: usize| {
+            let mut d = 0;
+            while v != 0 {
+                v = par[v];
+                d += 1;
+            }
+            d
+        };
+        let (mut a, mut b) = (vec![], vec![]);
+        while u != v {
+            if depth(u) >= depth(v) {
+                a.push(u);
+                u = par[u];
+            } else {
+                b.push(v);
+                v = par[v];
+            }
+        }
+        a.push(u);
+        a.extend(b.into_iter().rev());
+        a
+    }
+
+    #[test]
+    fn hld_queries() {
+        let mut rand = Pcg::seed_from_u64(4301);
+        for _ in 0..20 {
+            let n = rand.next_u32() as usize % 50 + 1;
+            let (par, g) = random_tree(&mut rand, n);
+            let hld = Hld::new(&g, 0);
+            let mut pos = vec![!0; n];
+            for v in 0..n {
+                pos[hld.index(v)] = v;
+            }
+            assert!(pos.iter().all(|&v| v < n));
+            for v in 0..n {
+                assert_eq!(hld.parent(v), Some(par[v]).filter(|&p| p != !0));
+                let r = hld.subtree_range(v);
+                for (i, &w) in pos.iter().enumerate() {
+                    let mut x = w;
+                    while x != !0 && x != v {
+                        x = par[x];
+                    }
+                    assert_eq!(r.contains(&i), x == v);
+                }
+                let mut a = v;
+                for k in 0..=n {
+                    assert_eq!(hld.la(v, k), Some(a).filter(|&a| a != !0));
+                    if a != !0 {
+                        a = par[a];
+                    }
+                }
+            }
+            for _ in 0..50 {
+                let u = rand.next_u32() as usize % n;
+                let v = rand.next_u32() as usize % n;
+                let path = naive_path(&par, u, v);
+                assert_eq!(hld.dist(u, v), path.len() - 1);
+                for edge in [false, true] {
+                    let mut got = vec![];
+                    for (r, rev) in hld.path_ranges(u, v, edge) {
+                        let mut vs: Vec<_> = r.map(|i| pos[i]).collect();
+                        if rev {
+                            vs.reverse();
+                        }
+                        got.extend(vs);
+                    }
+                    let w = hld.lca(u, v);
+                    let expected: Vec<_> =
+                        path.iter().copied().filter(|&x| !edge || x != w).collect();
+                    assert_eq!(got, expected);
+                }
+            }
+        }
+    }
+
+    // x -> ax + b mod 998244353, applied left to right
+    #[derive(Clone, Copy, PartialEq, Debug)]
+    struct Affine(u64, u64);
+    const MOD: u64 = 998244353;
+    impl Monoid for Affine {
+        fn id() -> Self {
+            Affine(1, 0)
+        }
+        fn op(&self, other: &Self) -> Self {
+            Affine(self.0 * other.0 % MOD, (self.1 * other.0 + other.1) % MOD)
+        }
+    }
+    // (sum, len)
+    #[derive(Clone, Copy, PartialEq, Debug)]
+    struct Sum(u64, u64);
+    impl Monoid for Sum {
+        fn id() -> Self {
+            Sum(0, 0)
+        }
+        fn op(&self, other: &Self) -> Self {
+            Sum(self.0 + other.0, self.1 + other.1)
+        }
+    }
+    struct Add(u64);
+    impl Monoid for Add {
+        fn id() -> Self {
+            Add(0)
+        }
+        fn op(&self, other: &Self) -> Self {
+            Add(self.0 + other.0)
+        }
+    }
+    impl Map<Sum> for Add {
+        fn map(&self, x: Sum) -> Sum {
+            Sum(x.0 + self.0 * x.1, x.1)
+        }
+    }
+
+    #[test]
+    fn hld_seg_tree() {
+        let mut rand = Pcg::seed_from_u64(4302);
+        for edge in [false, true] {
+            let n = 60;
+            let (par, g) = random_tree(&mut rand, n);
+            let mut a: Vec<_> = (0..n)
+                .map(|_| Affine(rand.next_u64() % MOD, rand.next_u64() % MOD))
+                .collect();
+            let mut st = HldSegTree::new(Hld::new(&g, 0), a.clone(), edge);
+            let mut b = vec![0; n];
+            let mut lst: HldLazySegTree<_, Add> =
+                HldLazySegTree::new(Hld::new(&g, 0), vec![Sum(0, 1); n], edge);
+            for _ in 0..500 {
+                let u = rand.next_u32() as usize % n;
+                let v = rand.next_u32() as usize % n;
+                let x = rand.next_u64() % 100;
+                let mut path = naive_path(&par, u, v);
+                if edge {
+                    let w = st.hld().lca(u, v);
+                    path.retain(|&x| x != w);
+                }
+                // in the order of positions
+                let mut sub: Vec<_> = (0..n)
+                    .filter(|&w| st.hld().subtree_range(u).contains(&st.hld().index(w)))
+                    .filter(|&w| !edge || w != u)
+                    .collect();
+                sub.sort_by_key(|&w| st.hld().index(w));
+                match rand.next_u32() % 4 {
+                    0 => {
+                        a[u] = Affine(x, rand.next_u64() % MOD);
+                        st.set(u, a[u]);
+                        lst.set(u, Sum(x, 1));
+                        b[u] = x;
+                    }
+                    1 => {
+                        lst.apply_path(u, v, Add(x));
+                        path.iter().for_each(|&w| b[w] += x);
+                    }
+                    2 => {
+                        lst.apply_subtree(u, Add(x));
+                        sub.iter().for_each(|&w| b[w] += x);
+                    }
+                    _ => {
+                        let e = path.iter().fold(Affine::id(), |s, &w| s.op(&a[w]));
+                        assert_eq!(st.prod_path(u, v), e);
+                        let e = sub.iter().fold(Affine::id(), |s, &w| s.op(&a[w]));
+                        assert_eq!(st.prod_subtree(u), e);
+                        let len = path.len() as u64;
+                        let e = Sum(path.iter().map(|&w| b[w]).sum(), len);
+                        assert_eq!(lst.prod_path(u, v), e);
+                        let e = Sum(sub.iter().map(|&w| b[w]).sum(), sub.len() as u64);
+                        assert_eq!(lst.prod_subtree(u), e);
+                        assert_eq!(*st.get(u), a[u]);
+                        assert_eq!(lst.get(u).0, b[u]);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn hld_long_path() {
+        let n = 1_000_000;
+        let mut g = vec![vec![]; n];
+        for u in 1..n {
+            g[u - 1].push(u);
+            g[u].push(u - 1);
+        }
+        let hld = Hld::new(&g, 0);
+        assert_eq!(hld.lca(n - 1, 12345), 12345);
+        let hld = Hld::new(&g, n / 2);
+        assert_eq!(hld.lca(0, n - 1), n / 2);
+        assert_eq!(hld.lca(3, 7), 7);
+    }
 }
@@ -1,64 +1,82 @@
-pub fn two_edge_connected_components(g: &[Vec<usize>]) -> Vec<usize> {
-    let ll = LowLink::new(g);
-    let mut cc = vec![!0; g.len()];
-    let mut col = 0;
-    for u in 0..g.len() {
-        if cc[u] == !0 {
-            tecc_dfs(&ll, u, &mut cc, col);
-            col += 1;
-        }
-    }
-    cc
+use crate::graph2::Graph;
+
+pub fn two_edge_connected_components<'a, T: 'a, G: Graph<'a, T> + ?Sized>(g: &'a G) -> Vec<usize> {
+    LowLink::new(g).two_edge_connected_components()
 }
-fn tecc_dfs(ll: &LowLink, u: usize, cc: &mut [usize], col: usize) {
-    cc[u] = col;
-    // detect multiedges
-    let mut appeared = std::collections::BTreeSet::new();
-    for &v in &ll.graph()[u] {
-        if cc[v] == !0 {
-            if !ll.is_bridge(u, v) || appeared.contains(&v) {
-                tecc_dfs(ll, v, cc, col);
-            } else {
-                appeared.insert(v);
-            }
-        }
-    }
+
+// the 2-edge-connected components and the bridges between them
+#[derive(Clone, Debug)]
+pub struct TwoEdgeTree {
+    pub comp: Vec<usize>,
+    // (the adjacent component, the id of the bridge)
+    pub tree: Vec<Vec<(usize, usize)>>,
 }
 
+// parallel edges are not bridges: the dfs skips one copy of the edge to the parent, which is
+// the tree edge, and the other copies count as back edges
 #[derive(Debug)]
-pub struct LowLink<'a> {
-    g: &'a [Vec<usize>],
+pub struct LowLink<'a, G: ?Sized> {
+    g: &'a G,
     ord: Vec<usize>,
     low: Vec<usize>,
+    par: Vec<usize>,
+    // the vertices in preorder
+    vs: Vec<usize>,
 }
-impl<'a> LowLink<'a> {
-    pub fn new(g: &'a [Vec<usize>]) -> Self {
+impl<'a, G: ?Sized> LowLink<'a, G> {
+    pub fn new<T: 'a>(g: &'a G) -> Self
+    where
+        G: Graph<'a, T>,
+    {
+        let n = g.len();
         let mut ll = Self {
             g,
-            ord: vec![!0; g.len()],
-            low: vec![!0; g.len()],
+            ord: vec![!0; n],
+            low: vec![!0; n],
+            par: vec![!0; n],
+            vs: Vec::with_capacity(n),
         };
-        let mut id = 0;
-        for u in 0..g.len() {
+        for u in 0..n {
             if ll.ord[u] == !0 {
-                ll.dfs(u, !0, &mut id);
+                ll.dfs(u);
             }
         }
         ll
     }
-    fn dfs(&mut self, u: usize, p: usize, id: &mut usize) {
-        self.ord[u] = *id;
-        self.low[u] = *id;
-        *id += 1;
-        for &v in self.g[u].iter().filter(|&&v| v != p) {
-            if self.ord[v] == !0 {
-                self.dfs(v, u, id);
+    fn enter(&mut self, u: usize) {
+        self.ord[u] = self.vs.len();
+        self.low[u] = self.vs.len();
+        self.vs.push(u);
+    }
+    fn dfs<T: 'a>(&mut self, r: usize)
+    where
+        G: Graph<'a, T>,
+    {
+        self.enter(r);
+        // (vertex, whether the edge to the parent is still to be skipped, adjacency)
+        let mut call = vec![(r, false, self.g.adj_unlabeled(r))];
+        while let Some((u, skip, it)) = call.last_mut() {
+            let u = *u;
+            if let Some(v) = it.next() {
+                if *skip && v == self.par[u] {
+                    *skip = false;
+                } else if self.ord[v] == !0 {
+                    self.par[v] = u;
+                    self.enter(v);
+                    call.push((v, true, self.g.adj_unlabeled(v)));
+                } else {
+                    self.low[u] = self.low[u].min(self.ord[v]);
+                }
+                continue;
+            }
+            call.pop();
+            if let Some(&(p, _, _)) = call.last() {
+                self.low[p] = self.low[p].min(self.low[u]);
             }
-            self.low[u] = self.low[u].min(self.low[v]);
         }
     }
-    pub fn graph(&self) -> &[Vec<usize>] {
-        &self.g
+    pub fn graph(&self) -> &'a G {
+        self.g
     }
     pub fn is_bridge(&self, mut u: usize, mut v: usize) -> bool {
         if self.ord[u] > self.ord[v] {
@@ -66,4 +84,282 @@ impl<'a> LowLink<'a> {
         }
         self.ord[u] < self.low[v]
     }
+    // the bridges as (parent, child) in the dfs tree
+    pub fn bridges(&self) -> Vec<(usize, usize)> {
+        self.vs
+            .iter()
+            .filter(|&&v| self.par[v] != !0 && self.ord[self.par[v]] < self.low[v])
+            .map(|&v| (self.par[v], v))
+            .collect()
+    }
+    pub fn articulation_points(&self) -> Vec<usize> {
+        let n = self.ord.len();
+        let mut children = vec![0; n];
+        let mut cut = vec![false; n];
+        for &v in &self.vs {
+            let p = self.par[v];
+            if p != !0 {
+                children[p] += 1;
+                cut[p] |= self.par[p] != !0 && self.ord[p] <= self.low[v];
+            }
+        }
+        (0..n)
+            .filter(|&u| cut[u] || self.par[u] == !0 && children[u] >= 2)
+            .collect()
+    }
+    pub fn two_edge_connected_components(&self) -> Vec<usize> {
+        let mut cc = vec![!0; self.ord.len()];
+        let mut k = 0;
+        for &v in &self.vs {
+            let p = self.par[v];
+            if p == !0 || self.is_bridge(p, v) {
+                cc[v] = k;
+                k += 1;
+            } else {
+                cc[v] = cc[p];
+            }
+        }
+        cc
+    }
+    // whether edges, in both directions, are exactly the adjacency lists
+    fn matches<T: 'a>(&self, edges: &[(usize, usize)]) -> bool
+    where
+        G: Graph<'a, T>,
+    {
+        let mut adj = vec![vec![]; self.ord.len()];
+        for &(u, v) in edges {
+            adj[u].push(v);
+            adj[v].push(u);
+        }
+        adj.iter_mut().enumerate().all(|(u, a)| {
+            let mut b: Vec<_> = self.g.adj_unlabeled(u).collect();
+            a.sort_unstable();
+            b.sort_unstable();
+            *a == b
+        })
+    }
+    // edges must be the edges of the graph; bridges are identified by their ids
+    pub fn two_edge_connected_tree<T: 'a>(&self, edges: &[(usize, usize)]) -> TwoEdgeTree
+    where
+        G: Graph<'a, T>,
+    {
+        assert!(self.matches(edges), "edges differ from the graph");
+        let comp = self.two_edge_connected_components();
+        let k = comp.iter().map(|&c| c + 1).max().unwrap_or(0);
+        let mut tree = vec![vec![]; k];
+        for (i, &(u, v)) in edges.iter().enumerate() {
+            if comp[u] != comp[v] {
+                tree[comp[u]].push((comp[v], i));
+                tree[comp[v]].push((comp[u], i));
+            }
+        }
+        TwoEdgeTree { comp, tree }
+    }
+    // the block of the edge from each non-root vertex to its parent
+    fn blocks(&self) -> (usize, Vec<usize>) {
+        let mut blk = vec![!0; self.ord.len()];
+        let mut k = 0;
+        for &v in &self.vs {
+            let p = self.par[v];
+            if p == !0 {
+                continue;
+            }
+            if self.ord[p] <= self.low[v] {
+                blk[v] = k;
+                k += 1;
+            } else {
+                blk[v] = blk[p];
+            }
+        }
+        (k, blk)
+    }
+    // the ids of the edges in each biconnected component; self-loops belong to none
+    // edges must be the edges of the graph, as in two_edge_connected_tree
+    pub fn biconnected_components<T: 'a>(&self, edges: &[(usize, usize)]) -> Vec<Vec<usize>>
+    where
+        G: Graph<'a, T>,
+    {
+        assert!(self.matches(edges), "edges differ from the graph");
+        let (k, blk) = self.blocks();
+        let mut res = vec![vec![]; k];
+        for (i, &(u, v)) in edges.iter().enumerate() {
+            if u != v {
+                // a back edge lies in the block of the tree edge above its lower end
+                let w = if self.ord[u] > self.ord[v] { u } else { v };
+                res[blk[w]].push(i);
+            }
+        }
+        res
+    }
+    // vertices are 0..n and the blocks n..; each block is adjacent to its vertices
+    // and isolated vertices have no block
+    pub fn block_cut_tree(&self) -> Vec<Vec<usize>> {
+        let n = self.ord.len();
+        let (k, blk) = self.blocks();
+        let mut res = vec![vec![]; n + k];
+        let mut seen = vec![false; k];
+        for &v in &self.vs {
+            let p = self.par[v];
+            if p == !0 {
+                continue;
+            }
+            let b = blk[v];
+            res[v].push(n + b);
+            res[n + b].push(v);
+            if !seen[b] {
+                seen[b] = true;
+                // the top vertex of the block
+                res[p].push(n + b);
+                res[n + b].push(p);
+            }
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{data_structure::dsu::Dsu, random::*};
+
+    #[test]
+    fn low_link_random() {
+        let mut rand = Pcg::seed_from_u64(4202);
+        for _ in 0..100 {
+            let n = rand.next_u32() as usize % 10 + 1;
+            let edges: Vec<_> = (0..rand.next_u32() % 15)
+                .map(|_| (rand.next_u32() as usize % n, rand.next_u32() as usize % n))
+                .collect();
+            // the graph without the edge e and the vertex x
+            let conn = |e: usize, x: usize| {
+                let mut dsu = Dsu::new(n);
+                for (i, &(u, v)) in edges.iter().enumerate() {
+                    if i != e && u != x && v != x {
+                        dsu.unite(u, v);
+                    }
+                }
+                dsu
+            };
+            let mut g = vec![vec![]; n];
+            for &(u, v) in &edges {
+                g[u].push(v);
+                g[v].push(u);
+            }
+            let ll = LowLink::new(&g);
+            let all = conn(!0, !0);
+            let count = |dsu: &Dsu, x: usize| (0..n).filter(|&u| u != x && dsu.is_root(u)).count();
+
+            let mut bridges = vec![];
+            // u and v are 2-edge-connected iff no single edge separates them
+            let mut same = vec![vec![true; n]; n];
+            for (i, &(u, v)) in edges.iter().enumerate() {
+                let c = conn(i, !0);
+                if u != v {
+                    assert_eq!(ll.is_bridge(u, v), !c.is_same(u, v));
+                }
+                if !c.is_same(u, v) {
+                    bridges.push((u.min(v), u.max(v)));
+                }
+                for (a, row) in same.iter_mut().enumerate() {
+                    for (b, x) in row.iter_mut().enumerate() {
+                        *x &= c.is_same(a, b);
+                    }
+                }
+            }
+            let mut got: Vec<_> = ll
+                .bridges()
+                .iter()
+                .map(|&(u, v)| (u.min(v), u.max(v)))
+                .collect();
+            got.sort();
+            bridges.sort();
+            assert_eq!(got, bridges);
+
+            let cc = two_edge_connected_components(&g);
+            for a in 0..n {
+                for b in 0..n {
+                    assert_eq!(cc[a] == cc[b], all.is_same(a, b) && same[a][b]);
+                }
+            }
+            let t = ll.two_edge_connected_tree(&edges);
+            assert_eq!(t.comp, cc);
+            let mut ids: Vec<_> = t.tree.iter().flatten().map(|e| e.1).collect();
+            ids.sort();
+            ids.dedup();
+            assert!(ids.iter().all(|&i| {
+                let (u, v) = edges[i];
+                ll.is_bridge(u, v) && t.tree[cc[u]].contains(&(cc[v], i))
+            }));
+            assert_eq!(ids.len(), bridges.len());
+
+            let cut: Vec<_> = (0..n)
+                .filter(|&x| count(&conn(!0, x), x) > count(&all, !0))
+                .collect();
+            assert_eq!(ll.articulation_points(), cut);
+
+            // two edges share a block iff no vertex separates what is left of them
+            let bcc = ll.biconnected_components(&edges);
+            let mut blk = vec![!0; edges.len()];
+            for (b, es) in bcc.iter().enumerate() {
+                assert!(!es.is_empty());
+                for &i in es {
+                    assert_eq!(blk[i], !0);
+                    blk[i] = b;
+                }
+            }
+            for (i, &(a, b)) in edges.iter().enumerate() {
+                assert_eq!(blk[i] == !0, a == b);
+                for (j, &(c, d)) in edges.iter().enumerate() {
+                    if a == b || c == d {
+                        continue;
+                    }
+                    let together = all.is_same(a, c)
+                        && (0..n).all(|x| {
+                            let dsu = conn(!0, x);
+                            let ends: Vec<_> =
+                                [a, b, c, d].iter().copied().filter(|&y| y != x).collect();
+                            ends.iter().all(|&y| dsu.is_same(y, ends[0]))
+                        });
+                    assert_eq!(blk[i] == blk[j], together);
+                }
+            }
+            let bct = ll.block_cut_tree();
+            assert_eq!(bct.len(), n + bcc.len());
+            for (b, es) in bcc.iter().enumerate() {
+                let mut vs: Vec<_> = es.iter().flat_map(|&i| [edges[i].0, edges[i].1]).collect();
+                vs.sort();
+                vs.dedup();
+                let mut adj = bct[n + b].clone();
+                adj.sort();
+                assert_eq!(adj, vs);
+            }
+            assert!((0..n).all(|u| bct[u].iter().all(|&b| b >= n && bct[b].contains(&u))));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn low_link_wrong_edges() {
+        let g = vec![vec![1, 1], vec![0, 0]];
+        LowLink::new(&g).two_edge_connected_tree(&[(0, 1)]);
+    }
+
+    #[test]
+    fn low_link_long_path() {
+        let n = 1_000_000;
+        let mut g = vec![vec![]; n];
+        for u in 1..n {
+            g[u - 1].push(u);
+            g[u].push(u - 1);
+        }
+        let ll = LowLink::new(&g);
+        assert!((1..n).all(|u| ll.is_bridge(u - 1, u)));
+        let cc = two_edge_connected_components(&g);
+        assert!(cc.iter().enumerate().all(|(u, &c)| c == u));
+        g[0].push(n - 1);
+        g[n - 1].push(0);
+        let ll = LowLink::new(&g);
+        assert!((1..n).all(|u| !ll.is_bridge(u - 1, u)));
+        assert!(two_edge_connected_components(&g).iter().all(|&c| c == 0));
+    }
 }
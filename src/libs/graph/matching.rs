@@ -0,0 +1,467 @@
+use super::min_cost_flow::Cost;
+use crate::graph2::Graph;
+use crate::other::matrix::Matrix;
+use std::collections::VecDeque;
+
+// maximum bipartite matching between the left vertices 0..n and the right vertices 0..m
+pub struct HopcroftKarp {
+    g: Vec<Vec<usize>>,
+    mate_l: Vec<usize>,
+    mate_r: Vec<usize>,
+}
+impl HopcroftKarp {
+    pub fn new(n: usize, m: usize) -> Self {
+        Self {
+            g: vec![vec![]; n],
+            mate_l: vec![!0; n],
+            mate_r: vec![!0; m],
+        }
+    }
+    pub fn add_edge(&mut self, u: usize, v: usize) {
+        assert!(v < self.mate_r.len());
+        self.g[u].push(v);
+    }
+    // the size of the maximum matching; can be called again after adding edges
+    pub fn run(&mut self) -> usize {
+        let n = self.g.len();
+        let mut dist = vec![0; n];
+        let mut it = vec![0; n];
+        let mut que = VecDeque::new();
+        loop {
+            // layers of the alternating paths from the free left vertices
+            for (u, d) in dist.iter_mut().enumerate() {
+                if self.mate_l[u] == !0 {
+                    *d = 0;
+                    que.push_back(u);
+                } else {
+                    *d = !0;
+                }
+            }
+            let mut found = false;
+            while let Some(u) = que.pop_front() {
+                for &v in &self.g[u] {
+                    let w = self.mate_r[v];
+                    if w == !0 {
+                        found = true;
+                    } else if dist[w] == !0 {
+                        dist[w] = dist[u] + 1;
+                        que.push_back(w);
+                    }
+                }
+            }
+            if !found {
+                break;
+            }
+            it.fill(0);
+            for s in 0..n {
+                if self.mate_l[s] == !0 {
+                    self.augment(s, &mut dist, &mut it);
+                }
+            }
+        }
+        self.mate_l.iter().filter(|&&v| v != !0).count()
+    }
+    fn augment(&mut self, s: usize, dist: &mut [usize], it: &mut [usize]) -> bool {
+        let mut stk = vec![s];
+        while let Some(&u) = stk.last() {
+            if it[u] == self.g[u].len() {
+                // no augmenting path through u in this phase
+                dist[u] = !0;
+                stk.pop();
+                if let Some(&p) = stk.last() {
+                    it[p] += 1;
+                }
+                continue;
+            }
+            let v = self.g[u][it[u]];
+            let w = self.mate_r[v];
+            if w == !0 {
+                for &u in &stk {
+                    let v = self.g[u][it[u]];
+                    self.mate_l[u] = v;
+                    self.mate_r[v] = u;
+                }
+                return true;
+            } else if dist[w] == dist[u] + 1 {
+                stk.push(w);
+            } else {
+                it[u] += 1;
+            }
+        }
+        false
+    }
+    pub fn mate_left(&self, u: usize) -> Option<usize> {
+        Some(self.mate_l[u]).filter(|&v| v != !0)
+    }
+    pub fn mate_right(&self, v: usize) -> Option<usize> {
+        Some(self.mate_r[v]).filter(|&u| u != !0)
+    }
+    pub fn pairs(&self) -> Vec<(usize, usize)> {
+        (0..self.g.len())
+            .filter_map(|u| self.mate_left(u).map(|v| (u, v)))
+            .collect()
+    }
+    // the vertices reachable from the free left vertices by alternating paths
+    fn reachable(&self) -> (Vec<bool>, Vec<bool>) {
+        let mut vis_l = vec![false; self.g.len()];
+        let mut vis_r = vec![false; self.mate_r.len()];
+        let mut stk = vec![];
+        for (u, vis) in vis_l.iter_mut().enumerate() {
+            if self.mate_l[u] == !0 {
+                *vis = true;
+                stk.push(u);
+            }
+        }
+        while let Some(u) = stk.pop() {
+            for &v in &self.g[u] {
+                if !vis_r[v] {
+                    vis_r[v] = true;
+                    let w = self.mate_r[v];
+                    if w != !0 && !vis_l[w] {
+                        vis_l[w] = true;
+                        stk.push(w);
+                    }
+                }
+            }
+        }
+        (vis_l, vis_r)
+    }
+    // König's theorem: the unreachable left and the reachable right vertices, after run
+    pub fn min_vertex_cover(&self) -> (Vec<usize>, Vec<usize>) {
+        let (vis_l, vis_r) = self.reachable();
+        (
+            (0..vis_l.len()).filter(|&u| !vis_l[u]).collect(),
+            (0..vis_r.len()).filter(|&v| vis_r[v]).collect(),
+        )
+    }
+    // the complement of the minimum vertex cover, after run
+    pub fn max_independent_set(&self) -> (Vec<usize>, Vec<usize>) {
+        let (vis_l, vis_r) = self.reachable();
+        (
+            (0..vis_l.len()).filter(|&u| vis_l[u]).collect(),
+            (0..vis_r.len()).filter(|&v| !vis_r[v]).collect(),
+        )
+    }
+}
+
+// minimum cost assignment of the rows to distinct columns (rows <= columns) in O(n^2 m)
+// returns the cost and the column of each row
+pub fn hungarian<T: Cost>(a: &Matrix<T>) -> (T, Vec<usize>) {
+    let (n, m) = a.dim();
+    assert!(n <= m);
+    let zero = T::default();
+    let mut u = vec![zero; n];
+    // the column m is a virtual one holding the row being inserted
+    let mut v = vec![zero; m + 1];
+    let mut p = vec![!0; m + 1];
+    let mut way = vec![m; m + 1];
+    for i in 0..n {
+        p[m] = i;
+        let mut j0 = m;
+        let mut minv = vec![T::MAX; m + 1];
+        let mut used = vec![false; m + 1];
+        loop {
+            used[j0] = true;
+            let i0 = p[j0];
+            let mut delta = T::MAX;
+            let mut j1 = m;
+            for j in 0..m {
+                if !used[j] {
+                    let cur = a[i0][j] - u[i0] - v[j];
+                    if cur < minv[j] {
+                        minv[j] = cur;
+                        way[j] = j0;
+                    }
+                    if minv[j] < delta {
+                        delta = minv[j];
+                        j1 = j;
+                    }
+                }
+            }
+            for j in 0..=m {
+                if used[j] {
+                    u[p[j]] = u[p[j]] + delta;
+                    v[j] = v[j] - delta;
+                } else {
+                    minv[j] = minv[j] - delta;
+                }
+            }
+            j0 = j1;
+            if p[j0] == !0 {
+                break;
+            }
+        }
+        while j0 != m {
+            let j1 = way[j0];
+            p[j0] = p[j1];
+            j0 = j1;
+        }
+    }
+    let mut col = vec![!0; n];
+    for (j, &i) in p[..m].iter().enumerate() {
+        if i != !0 {
+            col[i] = j;
+        }
+    }
+    let cost = (0..n).fold(zero, |s, i| s + a[i][col[i]]);
+    (cost, col)
+}
+
+// maximum matching in a general graph by Edmonds' blossom algorithm in O(n (n^2 + m))
+// returns the size and the mate of each vertex
+pub fn general_matching<'a, T: 'a, G: Graph<'a, T> + ?Sized>(
+    g: &'a G,
+) -> (usize, Vec<Option<usize>>) {
+    let n = g.len();
+    let mut b = Blossom {
+        mate: vec![!0; n],
+        p: vec![!0; n],
+        base: (0..n).collect(),
+        used: vec![false; n],
+        blossom: vec![false; n],
+    };
+    // greedy initial matching
+    for u in 0..n {
+        if b.mate[u] == !0 {
+            if let Some(v) = g.adj_unlabeled(u).find(|&v| v != u && b.mate[v] == !0) {
+                b.mate[u] = v;
+                b.mate[v] = u;
+            }
+        }
+    }
+    for r in 0..n {
+        if b.mate[r] == !0 {
+            let mut v = b.find_path(g, r);
+            while v != !0 {
+                let pv = b.p[v];
+                let ppv = b.mate[pv];
+                b.mate[v] = pv;
+                b.mate[pv] = v;
+                v = ppv;
+            }
+        }
+    }
+    let size = b.mate.iter().filter(|&&v| v != !0).count() / 2;
+    (
+        size,
+        b.mate
+            .into_iter()
+            .map(|v| Some(v).filter(|&v| v != !0))
+            .collect(),
+    )
+}
+struct Blossom {
+    mate: Vec<usize>,
+    // the parent in the alternating tree of the outer-reached inner vertices
+    p: Vec<usize>,
+    // the base of the contracted blossom containing each vertex
+    base: Vec<usize>,
+    used: Vec<bool>,
+    blossom: Vec<bool>,
+}
+impl Blossom {
+    fn lca(&self, mut a: usize, mut b: usize) -> usize {
+        let mut seen = vec![false; self.mate.len()];
+        loop {
+            a = self.base[a];
+            seen[a] = true;
+            if self.mate[a] == !0 {
+                break;
+            }
+            a = self.p[self.mate[a]];
+        }
+        loop {
+            b = self.base[b];
+            if seen[b] {
+                return b;
+            }
+            b = self.p[self.mate[b]];
+        }
+    }
+    fn mark_path(&mut self, mut v: usize, b: usize, mut child: usize) {
+        while self.base[v] != b {
+            self.blossom[self.base[v]] = true;
+            self.blossom[self.base[self.mate[v]]] = true;
+            self.p[v] = child;
+            child = self.mate[v];
+            v = self.p[self.mate[v]];
+        }
+    }
+    // the free end of an augmenting path from the root, or !0
+    fn find_path<'a, T: 'a, G: Graph<'a, T> + ?Sized>(&mut self, g: &'a G, root: usize) -> usize {
+        let n = self.mate.len();
+        self.used.fill(false);
+        self.p.fill(!0);
+        for (i, b) in self.base.iter_mut().enumerate() {
+            *b = i;
+        }
+        self.used[root] = true;
+        let mut que = VecDeque::from(vec![root]);
+        while let Some(v) = que.pop_front() {
+            for to in g.adj_unlabeled(v) {
+                if self.base[v] == self.base[to] || self.mate[v] == to {
+                    continue;
+                }
+                if to == root || self.mate[to] != !0 && self.p[self.mate[to]] != !0 {
+                    // an odd cycle: contract the blossom
+                    let cur = self.lca(v, to);
+                    self.blossom.fill(false);
+                    self.mark_path(v, cur, to);
+                    self.mark_path(to, cur, v);
+                    for i in 0..n {
+                        if self.blossom[self.base[i]] {
+                            self.base[i] = cur;
+                            if !self.used[i] {
+                                self.used[i] = true;
+                                que.push_back(i);
+                            }
+                        }
+                    }
+                } else if self.p[to] == !0 {
+                    self.p[to] = v;
+                    if self.mate[to] == !0 {
+                        return to;
+                    }
+                    let w = self.mate[to];
+                    self.used[w] = true;
+                    que.push_back(w);
+                }
+            }
+        }
+        !0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::max_flow::Dinic;
+    use crate::random::*;
+
+    #[test]
+    fn hopcroft_karp() {
+        let mut rand = Pcg::seed_from_u64(5001);
+        for _ in 0..100 {
+            let n = rand.next_u32() as usize % 30 + 1;
+            let m = rand.next_u32() as usize % 30 + 1;
+            let k = rand.next_u32() as usize % 100;
+            let edges: Vec<_> = (0..k)
+                .map(|_| (rand.next_u32() as usize % n, rand.next_u32() as usize % m))
+                .collect();
+            let mut hk = HopcroftKarp::new(n, m);
+            let mut dinic = Dinic::new(n + m + 2);
+            for &(u, v) in &edges {
+                hk.add_edge(u, v);
+                dinic.add_edge(u, n + v, 1);
+            }
+            for u in 0..n {
+                dinic.add_edge(n + m, u, 1);
+            }
+            for v in 0..m {
+                dinic.add_edge(n + v, n + m + 1, 1);
+            }
+            let size = hk.run();
+            assert_eq!(size as i64, dinic.run(n + m, n + m + 1));
+            let pairs = hk.pairs();
+            assert_eq!(pairs.len(), size);
+            for &(u, v) in &pairs {
+                assert!(edges.contains(&(u, v)));
+                assert_eq!(hk.mate_right(v), Some(u));
+            }
+            let (cl, cr) = hk.min_vertex_cover();
+            assert_eq!(cl.len() + cr.len(), size);
+            for &(u, v) in &edges {
+                assert!(cl.contains(&u) || cr.contains(&v));
+            }
+            let (il, ir) = hk.max_independent_set();
+            assert_eq!(il.len() + ir.len(), n + m - size);
+            for &(u, v) in &edges {
+                assert!(!(il.contains(&u) && ir.contains(&v)));
+            }
+        }
+        // a long augmenting path
+        let n = 1_000_000;
+        let mut hk = HopcroftKarp::new(n, n);
+        for u in 0..n - 1 {
+            hk.add_edge(u, u + 1);
+        }
+        assert_eq!(hk.run(), n - 1);
+        for u in 0..n {
+            hk.add_edge(u, u);
+        }
+        assert_eq!(hk.run(), n);
+    }
+
+    #[test]
+    fn hungarian_assignment() {
+        let mut rand = Pcg::seed_from_u64(5002);
+        for _ in 0..100 {
+            let n = rand.next_u32() as usize % 6 + 1;
+            let m = n + rand.next_u32() as usize % 3;
+            let a = Matrix::repeat_with(n, m, || (rand.next_u32() % 201) as i64 - 100);
+            let (cost, col) = hungarian(&a);
+            assert_eq!(cost, (0..n).map(|i| a[i][col[i]]).sum::<i64>());
+            let mut seen = vec![false; m];
+            for &j in &col {
+                assert!(!seen[j]);
+                seen[j] = true;
+            }
+            // every injection by brute force
+            fn dfs(a: &Matrix<i64>, i: usize, used: &mut [bool], s: i64, best: &mut i64) {
+                if i == a.len() {
+                    *best = (*best).min(s);
+                    return;
+                }
+                for j in 0..used.len() {
+                    if !used[j] {
+                        used[j] = true;
+                        dfs(a, i + 1, used, s + a[i][j], best);
+                        used[j] = false;
+                    }
+                }
+            }
+            let mut best = i64::MAX;
+            dfs(&a, 0, &mut vec![false; m], 0, &mut best);
+            assert_eq!(cost, best);
+        }
+    }
+
+    #[test]
+    fn general_matching_random() {
+        let mut rand = Pcg::seed_from_u64(5003);
+        for _ in 0..200 {
+            let n = rand.next_u32() as usize % 14 + 1;
+            let k = rand.next_u32() as usize % 30;
+            let edges: Vec<_> = (0..k)
+                .map(|_| (rand.next_u32() as usize % n, rand.next_u32() as usize % n))
+                .collect();
+            let mut g = vec![vec![]; n];
+            for &(u, v) in &edges {
+                g[u].push(v);
+                g[v].push(u);
+            }
+            let (size, mate) = general_matching(&g);
+            assert_eq!(mate.iter().filter(|v| v.is_some()).count(), 2 * size);
+            for (u, &v) in mate.iter().enumerate() {
+                if let Some(v) = v {
+                    assert_ne!(u, v);
+                    assert_eq!(mate[v], Some(u));
+                    assert!(g[u].contains(&v));
+                }
+            }
+            // the maximum matching of each vertex subset
+            let mut dp = vec![0; 1 << n];
+            for s in 1..1usize << n {
+                let u = s.trailing_zeros() as usize;
+                let t = s & !(1 << u);
+                dp[s] = dp[t];
+                for &v in &g[u] {
+                    if v != u && t >> v & 1 == 1 {
+                        dp[s] = dp[s].max(dp[t & !(1 << v)] + 1);
+                    }
+                }
+            }
+            assert_eq!(size, dp[(1 << n) - 1]);
+        }
+    }
+}
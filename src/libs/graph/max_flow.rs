@@ -1,75 +1,401 @@
-pub struct Dinic {
-    g: Vec<Vec<(usize, bool)>>,
-    es: Vec<(usize, usize, i64)>,
+use std::collections::VecDeque;
+use std::ops::{Add, Sub};
+
+pub trait Capacity: Copy + Ord + Default + Add<Output = Self> + Sub<Output = Self> {
+    const MAX: Self;
 }
-impl Dinic {
+macro_rules! capacity_impl {
+    ($($T:ident)*) => {$(
+        impl Capacity for $T {
+            const MAX: Self = $T::MAX;
+        }
+    )*};
+}
+capacity_impl!(i32 i64 i128 isize u32 u64 u128 usize);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Edge<C> {
+    pub from: usize,
+    pub to: usize,
+    pub cap: C,
+    pub flow: C,
+}
+
+// the arc 2i is the edge i and 2i + 1 is its reverse; each keeps its residual capacity
+#[derive(Clone)]
+pub struct Dinic<C = i64> {
+    g: Vec<Vec<usize>>,
+    arcs: Vec<(usize, C)>,
+}
+impl Dinic<i64> {
     pub fn new(n: usize) -> Self {
+        Self::with_capacity_type(n)
+    }
+}
+impl<C: Capacity> Dinic<C> {
+    // e.g. Dinic::<u32>::with_capacity_type(n)
+    pub fn with_capacity_type(n: usize) -> Self {
         Self {
             g: vec![Vec::new(); n],
-            es: Vec::new(),
+            arcs: Vec::new(),
         }
     }
-    pub fn add_edge(&mut self, u: usize, v: usize, cap: i64) {
-        self.g[u].push((self.es.len(), true));
-        self.g[v].push((self.es.len(), false));
-        self.es.push((u, v, cap));
-    }
-    fn edge(&self, flow: &[i64], i: usize, fw: bool) -> (usize, i64) {
-        let (u, v, c) = self.es[i];
-        if fw {
-            (v, c - flow[i])
-        } else {
-            (u, flow[i])
+    pub fn len(&self) -> usize {
+        self.g.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.g.is_empty()
+    }
+    // returns the id of the edge
+    pub fn add_edge(&mut self, u: usize, v: usize, cap: C) -> usize {
+        assert!(cap >= C::default());
+        let i = self.arcs.len() / 2;
+        self.g[u].push(2 * i);
+        self.g[v].push(2 * i + 1);
+        self.arcs.push((v, cap));
+        self.arcs.push((u, C::default()));
+        i
+    }
+    pub fn get_edge(&self, i: usize) -> Edge<C> {
+        let (to, r) = self.arcs[2 * i];
+        let (from, flow) = self.arcs[2 * i + 1];
+        Edge {
+            from,
+            to,
+            cap: r + flow,
+            flow,
         }
     }
-    pub fn run(&self, s: usize, t: usize) -> (i64, Vec<i64>) {
-        let mut flow = vec![0; self.es.len()];
-        let mut sum = 0;
-        loop {
-            let mut dist = vec![!0; self.g.len()];
+    pub fn edges(&self) -> Vec<Edge<C>> {
+        (0..self.arcs.len() / 2).map(|i| self.get_edge(i)).collect()
+    }
+    pub fn change_edge(&mut self, i: usize, cap: C, flow: C) {
+        assert!(C::default() <= flow && flow <= cap);
+        self.arcs[2 * i].1 = cap - flow;
+        self.arcs[2 * i + 1].1 = flow;
+    }
+    // pushes as much additional flow as possible; later runs continue from the current flow
+    pub fn run(&mut self, s: usize, t: usize) -> C {
+        self.run_with_limit(s, t, C::MAX)
+    }
+    pub fn run_with_limit(&mut self, s: usize, t: usize, limit: C) -> C {
+        assert!(s != t);
+        let n = self.g.len();
+        let mut flow = C::default();
+        let mut dist = vec![!0; n];
+        let mut cur = vec![0; n];
+        let mut que = VecDeque::new();
+        let mut stk = vec![];
+        while flow < limit {
+            dist.iter_mut().for_each(|d| *d = !0);
             dist[s] = 0;
-            let mut que = std::collections::VecDeque::new();
             que.push_back(s);
-            'bfs: while let Some(u) = que.pop_front() {
-                for (v, r) in self.g[u].iter().map(|&(i, fw)| self.edge(&flow, i, fw)) {
-                    if dist[v] == !0 && r > 0 {
+            while let Some(u) = que.pop_front() {
+                for &a in &self.g[u] {
+                    let (v, r) = self.arcs[a];
+                    if r > C::default() && dist[v] == !0 {
                         dist[v] = dist[u] + 1;
-                        if v == t {
-                            break 'bfs;
-                        }
                         que.push_back(v);
                     }
                 }
             }
-            let add = self.dfs(&mut flow, &mut dist, s, t, i64::max_value());
-            if add == 0 {
+            if dist[t] == !0 {
                 break;
             }
-            sum += add;
+            cur.iter_mut().for_each(|c| *c = 0);
+            // finds the augmenting paths one by one along the current arcs
+            while flow < limit {
+                stk.clear();
+                stk.push(s);
+                'dfs: while let Some(&u) = stk.last() {
+                    if u == t {
+                        break;
+                    }
+                    while let Some(&a) = self.g[u].get(cur[u]) {
+                        let (v, r) = self.arcs[a];
+                        if r > C::default() && dist[u] + 1 == dist[v] {
+                            stk.push(v);
+                            continue 'dfs;
+                        }
+                        cur[u] += 1;
+                    }
+                    dist[u] = !0;
+                    stk.pop();
+                }
+                if stk.is_empty() {
+                    break;
+                }
+                let path = &stk[..stk.len() - 1];
+                let add = path
+                    .iter()
+                    .map(|&u| self.arcs[self.g[u][cur[u]]].1)
+                    .fold(limit - flow, C::min);
+                for &u in path {
+                    let a = self.g[u][cur[u]];
+                    self.arcs[a].1 = self.arcs[a].1 - add;
+                    self.arcs[a ^ 1].1 = self.arcs[a ^ 1].1 + add;
+                }
+                flow = flow + add;
+            }
         }
-        (sum, flow)
+        flow
     }
-    fn dfs(&self, flow: &mut [i64], dist: &mut [usize], u: usize, t: usize, cap: i64) -> i64 {
-        if u == t {
-            return cap;
+    // the vertices reachable from s in the residual graph; after a run from s to t,
+    // the edges leaving them form a minimum cut
+    pub fn min_cut(&self, s: usize) -> Vec<bool> {
+        let mut res = vec![false; self.g.len()];
+        res[s] = true;
+        let mut stk = vec![s];
+        while let Some(u) = stk.pop() {
+            for &a in &self.g[u] {
+                let (v, r) = self.arcs[a];
+                if r > C::default() && !res[v] {
+                    res[v] = true;
+                    stk.push(v);
+                }
+            }
         }
-        let mut add = 0;
-        for &(i, fw) in &self.g[u] {
-            let (v, r) = self.edge(&flow, i, fw);
-            if dist[v] == dist[u] + 1 && r > 0 {
-                let a = self.dfs(flow, dist, v, t, (cap - add).min(r));
-                if a > 0 {
-                    if fw {
-                        flow[i] += a;
-                    } else {
-                        flow[i] -= a;
-                    }
-                    add += a;
-                } else {
-                    dist[v] = 0;
+        res
+    }
+}
+
+// flows where each edge carries between its lower and upper bounds; the demands of the
+// lower bounds are met through a super source and sink
+pub struct LowerBoundFlow<C = i64> {
+    dinic: Dinic<C>,
+    n: usize,
+    // (the id in dinic, the lower bound) of each edge
+    ids: Vec<(usize, C)>,
+    demand: C,
+}
+impl LowerBoundFlow<i64> {
+    pub fn new(n: usize) -> Self {
+        Self::with_capacity_type(n)
+    }
+}
+impl<C: Capacity> LowerBoundFlow<C> {
+    pub fn with_capacity_type(n: usize) -> Self {
+        let mut dinic = Dinic::with_capacity_type(n + 2);
+        for v in 0..n {
+            dinic.add_edge(n, v, C::default());
+            dinic.add_edge(v, n + 1, C::default());
+        }
+        Self {
+            dinic,
+            n,
+            ids: Vec::new(),
+            demand: C::default(),
+        }
+    }
+    pub fn add_edge(&mut self, u: usize, v: usize, lower: C, upper: C) -> usize {
+        assert!(C::default() <= lower && lower <= upper);
+        let id = self.dinic.add_edge(u, v, upper - lower);
+        // keeps the current flow so that the edges can be added after a run
+        for &i in &[2 * v, 2 * u + 1] {
+            let e = self.dinic.get_edge(i);
+            self.dinic.change_edge(i, e.cap + lower, e.flow);
+        }
+        self.demand = self.demand + lower;
+        self.ids.push((id, lower));
+        self.ids.len() - 1
+    }
+    // the flow of the edge i
+    pub fn flow(&self, i: usize) -> C {
+        let (id, lower) = self.ids[i];
+        lower + self.dinic.get_edge(id).flow
+    }
+    // finds a circulation within the bounds
+    pub fn feasible(&mut self) -> bool {
+        let n = self.n;
+        self.dinic.run(n, n + 1);
+        let f = (0..n).fold(C::default(), |f, v| f + self.dinic.get_edge(2 * v).flow);
+        f == self.demand
+    }
+    // the maximum flow from s to t within the bounds, if any; the flow is kept and
+    // feasible and max_flow should not be called again
+    pub fn max_flow(&mut self, s: usize, t: usize) -> Option<C> {
+        let back = self.dinic.add_edge(t, s, C::MAX);
+        if !self.feasible() {
+            return None;
+        }
+        let f = self.dinic.get_edge(back).flow;
+        self.dinic.change_edge(back, C::default(), C::default());
+        Some(f + self.dinic.run(s, t))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::*;
+
+    fn random_edges(rand: &mut Pcg, n: usize, m: usize, c: u32) -> Vec<(usize, usize, u32)> {
+        (0..m)
+            .map(|_| {
+                let u = rand.next_u32() as usize % n;
+                let v = rand.next_u32() as usize % n;
+                (u, v, rand.next_u32() % c)
+            })
+            .collect()
+    }
+    fn min_cut_naive(n: usize, edges: &[(usize, usize, u32)], s: usize, t: usize) -> u32 {
+        (0..1usize << n)
+            .filter(|&x| x >> s & 1 == 1 && x >> t & 1 == 0)
+            .map(|x| {
+                edges
+                    .iter()
+                    .filter(|e| x >> e.0 & 1 == 1 && x >> e.1 & 1 == 0)
+                    .map(|e| e.2)
+                    .sum()
+            })
+            .min()
+            .unwrap()
+    }
+    fn check_flow(dinic: &Dinic<u32>, s: usize, t: usize, f: u32) {
+        let mut excess = vec![0i64; dinic.len()];
+        for e in dinic.edges() {
+            assert!(e.flow <= e.cap);
+            excess[e.from] -= e.flow as i64;
+            excess[e.to] += e.flow as i64;
+        }
+        for (v, &x) in excess.iter().enumerate() {
+            let e = if v == s {
+                -(f as i64)
+            } else if v == t {
+                f as i64
+            } else {
+                0
+            };
+            assert_eq!(x, e);
+        }
+    }
+
+    #[test]
+    fn dinic_random() {
+        let mut rand = Pcg::seed_from_u64(4801);
+        for _ in 0..200 {
+            let n = rand.next_u32() as usize % 7 + 2;
+            let m = rand.next_u32() as usize % 20;
+            let edges = random_edges(&mut rand, n, m, 10);
+            let s = rand.next_u32() as usize % n;
+            let t = (s + 1 + rand.next_u32() as usize % (n - 1)) % n;
+            let k = edges.len() / 2;
+
+            // half of the edges first, then the rest incrementally
+            let mut dinic = Dinic::<u32>::with_capacity_type(n);
+            for &(u, v, c) in &edges[..k] {
+                dinic.add_edge(u, v, c);
+            }
+            let f1 = dinic.run(s, t);
+            assert_eq!(f1, min_cut_naive(n, &edges[..k], s, t));
+            for &(u, v, c) in &edges[k..] {
+                dinic.add_edge(u, v, c);
+            }
+            let f = f1 + dinic.run(s, t);
+            assert_eq!(f, min_cut_naive(n, &edges, s, t));
+            check_flow(&dinic, s, t, f);
+            let cut = dinic.min_cut(s);
+            assert!(cut[s] && !cut[t]);
+            let w: u32 = edges
+                .iter()
+                .filter(|e| cut[e.0] && !cut[e.1])
+                .map(|e| e.2)
+                .sum();
+            assert_eq!(w, f);
+
+            // new capacities from scratch
+            let mut edges = edges;
+            for (i, e) in edges.iter_mut().enumerate() {
+                e.2 = rand.next_u32() % 10;
+                dinic.change_edge(i, e.2, 0);
+            }
+            let limit = rand.next_u32() % 15;
+            let f = min_cut_naive(n, &edges, s, t);
+            assert_eq!(dinic.run_with_limit(s, t, limit), f.min(limit));
+            assert_eq!(dinic.run(s, t), f - f.min(limit));
+            check_flow(&dinic, s, t, f);
+        }
+    }
+
+    #[test]
+    fn dinic_i64() {
+        let mut dinic = Dinic::new(2);
+        dinic.add_edge(0, 1, 2_000_000_000);
+        dinic.add_edge(0, 1, 2_000_000_000);
+        assert_eq!(dinic.run(0, 1), 4_000_000_000);
+    }
+
+    #[test]
+    fn lower_bound_flow() {
+        let mut rand = Pcg::seed_from_u64(4802);
+        for _ in 0..300 {
+            let n = rand.next_u32() as usize % 4 + 2;
+            let m = rand.next_u32() as usize % 6;
+            let edges: Vec<_> = random_edges(&mut rand, n, m, 3)
+                .into_iter()
+                .map(|(u, v, lo)| (u, v, lo as i64, (lo + rand.next_u32() % 3) as i64))
+                .collect();
+            let (s, t) = (0, n - 1);
+            // every flow within the bounds
+            let mut circulation = false;
+            let mut best = None;
+            let mut f = vec![0; m];
+            let total: usize = edges.iter().map(|e| (e.3 - e.2 + 1) as usize).product();
+            for mut x in 0..total {
+                for (i, e) in edges.iter().enumerate() {
+                    let k = (e.3 - e.2 + 1) as usize;
+                    f[i] = e.2 + (x % k) as i64;
+                    x /= k;
+                }
+                let mut excess = vec![0; n];
+                for (i, e) in edges.iter().enumerate() {
+                    excess[e.0] -= f[i];
+                    excess[e.1] += f[i];
+                }
+                if excess.iter().all(|&x| x == 0) {
+                    circulation = true;
+                }
+                if (1..n - 1).all(|v| excess[v] == 0) && excess[t] >= 0 {
+                    best = best.max(Some(excess[t]));
+                }
+            }
+            let mut lbf = LowerBoundFlow::new(n);
+            // half of the edges are added after a run
+            for &(u, v, lo, hi) in &edges[..edges.len() / 2] {
+                lbf.add_edge(u, v, lo, hi);
+            }
+            lbf.feasible();
+            for &(u, v, lo, hi) in &edges[edges.len() / 2..] {
+                lbf.add_edge(u, v, lo, hi);
+            }
+            assert_eq!(lbf.feasible(), circulation);
+            if circulation {
+                let mut excess = vec![0; n];
+                for (i, e) in edges.iter().enumerate() {
+                    let x = lbf.flow(i);
+                    assert!(e.2 <= x && x <= e.3);
+                    excess[e.0] -= x;
+                    excess[e.1] += x;
+                }
+                assert!(excess.iter().all(|&x| x == 0));
+            }
+
+            let mut lbf = LowerBoundFlow::new(n);
+            for &(u, v, lo, hi) in &edges {
+                lbf.add_edge(u, v, lo, hi);
+            }
+            let res = lbf.max_flow(s, t);
+            assert_eq!(res, best);
+            if let Some(best) = best {
+                let mut excess = vec![0; n];
+                for (i, e) in edges.iter().enumerate() {
+                    let x = lbf.flow(i);
+                    assert!(e.2 <= x && x <= e.3);
+                    excess[e.0] -= x;
+                    excess[e.1] += x;
                 }
+                assert_eq!(excess[t], best);
             }
         }
-        add
     }
 }
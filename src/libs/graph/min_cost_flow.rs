@@ -1,90 +1,283 @@
-pub struct MinCostFlow {
-    g: Vec<Vec<Edge>>,
+use super::max_flow::Capacity;
+use std::ops::{Mul, Neg};
+
+pub trait Cost: Capacity + Neg<Output = Self> + Mul<Output = Self> {}
+impl<T: Capacity + Neg<Output = T> + Mul<Output = T>> Cost for T {}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Edge<C, D> {
+    pub from: usize,
+    pub to: usize,
+    pub cap: C,
+    pub flow: C,
+    pub cost: D,
+}
+
+// primal-dual with potentials; negative costs are allowed as long as there is no
+// negative cycle
+pub struct MinCostFlow<C = i32, D = i64> {
+    g: Vec<Vec<Arc<C, D>>>,
+    // the vertex and the index in its list of each edge
+    pos: Vec<(usize, usize)>,
 }
 #[derive(Clone, Copy)]
-struct Edge {
+struct Arc<C, D> {
     v: usize,
     rev: usize,
-    cap: i32,
-    cost: i64,
+    cap: C,
+    cost: D,
 }
-impl MinCostFlow {
+impl MinCostFlow<i32, i64> {
     pub fn new(n: usize) -> Self {
+        Self::with_types(n)
+    }
+}
+impl<C: Capacity, D: Cost + From<C>> MinCostFlow<C, D> {
+    // e.g. MinCostFlow::<i64, i64>::with_types(n)
+    pub fn with_types(n: usize) -> Self {
         Self {
             g: vec![Vec::new(); n],
+            pos: Vec::new(),
         }
     }
-    pub fn add_edge(&mut self, u: usize, v: usize, cap: i32, cost: i64) {
-        let rev_u = self.g[v].len();
+    // returns the id of the edge
+    pub fn add_edge(&mut self, u: usize, v: usize, cap: C, cost: D) -> usize {
+        assert!(cap >= C::default());
+        let rev_u = self.g[v].len() + (u == v) as usize;
         let rev_v = self.g[u].len();
-        self.g[u].push(Edge {
+        self.pos.push((u, rev_v));
+        self.g[u].push(Arc {
             v,
             rev: rev_u,
             cap,
             cost,
         });
-        self.g[v].push(Edge {
+        self.g[v].push(Arc {
             v: u,
             rev: rev_v,
-            cap: 0,
+            cap: C::default(),
             cost: -cost,
-        })
+        });
+        self.pos.len() - 1
+    }
+    pub fn get_edge(&self, i: usize) -> Edge<C, D> {
+        let (u, j) = self.pos[i];
+        let e = self.g[u][j];
+        let r = self.g[e.v][e.rev];
+        Edge {
+            from: u,
+            to: e.v,
+            cap: e.cap + r.cap,
+            flow: r.cap,
+            cost: e.cost,
+        }
+    }
+    pub fn edges(&self) -> Vec<Edge<C, D>> {
+        (0..self.pos.len()).map(|i| self.get_edge(i)).collect()
+    }
+    // (flow, cost) of the minimum cost flow of at most limit
+    pub fn run(&mut self, s: usize, t: usize, limit: C) -> (C, D) {
+        *self.slope(s, t, limit).last().unwrap()
     }
-    pub fn run(&mut self, s: usize, t: usize, limit: i32) -> (i32, i64) {
-        use std::cmp::Reverse;
+    // the breakpoints of the minimum cost as a function of the flow, from (0, 0) up to the
+    // maximum flow within limit; later runs continue from the current flow
+    pub fn slope(&mut self, s: usize, t: usize, limit: C) -> Vec<(C, D)> {
         let n = self.g.len();
-        let mut flow = 0;
-        let mut cost = 0;
-        let mut p = vec![0; n];
-        let mut dist = vec![0; n];
+        let zero = D::default();
+        let mut res = vec![(C::default(), zero)];
+        if s == t {
+            return res;
+        }
+        let mut p = self.initial_potentials(s);
+        let mut dist: Vec<Option<D>> = vec![None; n];
         let mut que = std::collections::BinaryHeap::new();
         let mut prev = vec![(0, 0); n];
-        while flow < limit {
-            dist.clear();
-            dist.resize(n, i64::max_value());
-            dist[s] = 0;
-            que.push((Reverse(0), s));
-            prev[t].0 = !0;
-            while let Some((Reverse(d), u)) = que.pop() {
-                if dist[u] < d {
+        let mut last_unit = None;
+        while res.last().unwrap().0 < limit {
+            dist.iter_mut().for_each(|d| *d = None);
+            dist[s] = Some(zero);
+            que.push((std::cmp::Reverse(zero), s));
+            while let Some((std::cmp::Reverse(d), u)) = que.pop() {
+                if dist[u] != Some(d) {
                     continue;
                 }
                 for (i, e) in self.g[u].iter().enumerate() {
-                    let dd = d + e.cost - p[e.v] + p[u];
-                    if e.cap > 0 && dd < dist[e.v] {
-                        dist[e.v] = dd;
-                        que.push((Reverse(dd), e.v));
+                    let dd = d + e.cost + p[u] - p[e.v];
+                    if e.cap > C::default() && dist[e.v].map(|x| dd < x).unwrap_or(true) {
+                        dist[e.v] = Some(dd);
+                        que.push((std::cmp::Reverse(dd), e.v));
                         prev[e.v] = (u, i);
                     }
                 }
             }
-            if prev[t].0 == !0 {
+            if dist[t].is_none() {
                 break;
             }
-            for u in 0..n {
-                if dist[u] != i64::max_value() {
-                    p[u] += dist[u];
+            for (pu, du) in p.iter_mut().zip(&dist) {
+                if let Some(d) = du {
+                    *pu = *pu + *d;
                 }
             }
-            let mut v = t;
+            let (flow, cost) = *res.last().unwrap();
             let mut add = limit - flow;
+            let mut v = t;
             while v != s {
                 let (u, i) = prev[v];
                 add = add.min(self.g[u][i].cap);
                 v = u;
             }
-            flow += add;
+            let mut unit = zero;
             let mut v = t;
             while v != s {
                 let (u, i) = prev[v];
                 let e = &mut self.g[u][i];
-                cost += e.cost * add as i64;
-                e.cap -= add;
+                unit = unit + e.cost;
+                e.cap = e.cap - add;
                 let rev = e.rev;
-                self.g[v][rev].cap += add;
+                let r = &mut self.g[v][rev];
+                r.cap = r.cap + add;
                 v = u;
             }
+            let next = (flow + add, cost + unit * D::from(add));
+            // merges the segments of the same slope
+            if last_unit == Some(unit) {
+                *res.last_mut().unwrap() = next;
+            } else {
+                res.push(next);
+            }
+            last_unit = Some(unit);
+        }
+        res
+    }
+    // the distances from s by Bellman-Ford if some residual edge has a negative cost
+    fn initial_potentials(&self, s: usize) -> Vec<D> {
+        let n = self.g.len();
+        let zero = D::default();
+        let mut p = vec![zero; n];
+        let residual = || self.g.iter().flatten().filter(|e| e.cap > C::default());
+        if residual().all(|e| e.cost >= zero) {
+            return p;
+        }
+        let mut dist = vec![None; n];
+        dist[s] = Some(zero);
+        for i in 0..=n {
+            let mut updated = false;
+            for u in 0..n {
+                let du = match dist[u] {
+                    Some(d) => d,
+                    None => continue,
+                };
+                for e in self.g[u].iter().filter(|e| e.cap > C::default()) {
+                    if dist[e.v].map(|x| du + e.cost < x).unwrap_or(true) {
+                        dist[e.v] = Some(du + e.cost);
+                        updated = true;
+                    }
+                }
+            }
+            if !updated {
+                break;
+            }
+            assert!(i < n, "negative cycle");
+        }
+        for (pu, d) in p.iter_mut().zip(dist) {
+            *pu = d.unwrap_or(zero);
+        }
+        p
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::network_simplex::NetworkSimplex;
+    use crate::random::*;
+
+    #[test]
+    fn min_cost_flow_default_types() {
+        let mut mcf = MinCostFlow::new(2);
+        mcf.add_edge(0, 1, 10, 1_000_000_000);
+        assert_eq!(mcf.run(0, 1, 10), (10, 10_000_000_000));
+        assert_eq!(mcf.run(0, 0, 10), (0, 0));
+        assert_eq!(mcf.slope(1, 1, 10), vec![(0, 0)]);
+    }
+
+    #[test]
+    fn min_cost_flow_slope() {
+        let mut rand = Pcg::seed_from_u64(4901);
+        for iter in 0..200 {
+            let n = rand.next_u32() as usize % 6 + 2;
+            let negative = iter & 1 == 1;
+            let edges: Vec<_> = (0..rand.next_u32() % 15)
+                .map(|_| {
+                    let mut u = rand.next_u32() as usize % n;
+                    let mut v = rand.next_u32() as usize % n;
+                    // no cycles when costs may be negative
+                    if negative && u > v {
+                        std::mem::swap(&mut u, &mut v);
+                    }
+                    let cost = (rand.next_u32() % 10) as i64 - if negative { 5 } else { 0 };
+                    (u, v, (rand.next_u32() % 5) as i32, cost)
+                })
+                .filter(|e| !negative || e.0 != e.1 || e.3 >= 0)
+                .collect();
+            let (s, t) = (0, n - 1);
+            let mut mcf = MinCostFlow::new(n);
+            for &(u, v, c, d) in &edges {
+                mcf.add_edge(u, v, c, d);
+            }
+            let slope = mcf.slope(s, t, i32::MAX);
+            let (f, cost) = *slope.last().unwrap();
+            assert_eq!(slope[0], (0, 0));
+            let es = mcf.edges();
+            let mut excess = vec![0; n];
+            for (e, &(u, v, c, d)) in es.iter().zip(&edges) {
+                assert_eq!((e.from, e.to, e.cap, e.cost), (u, v, c, d));
+                assert!(0 <= e.flow && e.flow <= e.cap);
+                excess[u] -= e.flow;
+                excess[v] += e.flow;
+            }
+            assert_eq!(excess[t], f);
+            assert_eq!(es.iter().map(|e| e.flow as i64 * e.cost).sum::<i64>(), cost);
+
+            // the cost of every flow value on the slope is the optimum of the b-flow
+            for k in 0..=f + 1 {
+                let mut ns = NetworkSimplex::new(n);
+                for &(u, v, c, d) in &edges {
+                    ns.add_edge(u, v, c as i64, d);
+                }
+                ns.add_supply(s, k as i64);
+                ns.add_supply(t, -k as i64);
+                let res = ns.solve();
+                if k > f {
+                    assert_eq!(res, None);
+                    continue;
+                }
+                let i = slope.partition_point(|p| p.0 < k);
+                let e = if slope[i].0 == k {
+                    slope[i].1
+                } else {
+                    let ((x0, y0), (x1, y1)) = (slope[i - 1], slope[i]);
+                    y0 + (y1 - y0) / (x1 - x0) as i64 * (k - x0) as i64
+                };
+                assert_eq!(res, Some(e));
+                for (i, &(u, v, c, d)) in edges.iter().enumerate() {
+                    let x = ns.flow(i);
+                    assert!(0 <= x && x <= c as i64);
+                    let r = d + ns.potential(u) - ns.potential(v);
+                    assert!(x == c as i64 || r >= 0);
+                    assert!(x == 0 || r <= 0);
+                }
+            }
+
+            // in two steps
+            let mut mcf = MinCostFlow::new(n);
+            for &(u, v, c, d) in &edges {
+                mcf.add_edge(u, v, c, d);
+            }
+            let k = f / 2;
+            let (f1, c1) = mcf.run(s, t, k);
+            let (f2, c2) = mcf.run(s, t, i32::MAX);
+            assert_eq!((f1 + f2, c1 + c2), (f, cost));
         }
-        (flow, cost)
     }
 }
@@ -1,10 +1,15 @@
 pub mod djikstra;
+pub mod dynamic_connectivity;
 pub mod hld;
 pub mod low_link;
+pub mod matching;
 pub mod max_flow;
 pub mod min_cost_flow;
+pub mod network_simplex;
 pub mod scc;
+pub mod shortest_path;
 pub mod tsort;
+pub mod two_sat;
 
 pub mod wip;
 
@@ -0,0 +1,274 @@
+use super::min_cost_flow::Cost;
+
+// minimum cost b-flow by the primal network simplex on a strongly feasible spanning tree,
+// rooted at an extra vertex joined to every vertex by an expensive artificial edge
+pub struct NetworkSimplex<D = i64> {
+    n: usize,
+    // the arc 2i is the edge i and 2i + 1 is its reverse: (to, residual capacity, cost)
+    arcs: Vec<(usize, D, D)>,
+    supply: Vec<D>,
+    pi: Vec<D>,
+}
+impl<D: Cost + From<bool>> NetworkSimplex<D> {
+    pub fn new(n: usize) -> Self {
+        let zero = D::default();
+        Self {
+            n,
+            arcs: Vec::new(),
+            supply: vec![zero; n],
+            pi: vec![zero; n],
+        }
+    }
+    // returns the id of the edge
+    pub fn add_edge(&mut self, u: usize, v: usize, cap: D, cost: D) -> usize {
+        assert!(u < self.n && v < self.n && cap >= D::default());
+        self.arcs.push((v, cap, cost));
+        self.arcs.push((u, D::default(), -cost));
+        self.arcs.len() / 2 - 1
+    }
+    // v sends out x more units; negative for a demand
+    pub fn add_supply(&mut self, v: usize, x: D) {
+        self.supply[v] = self.supply[v] + x;
+    }
+    pub fn flow(&self, i: usize) -> D {
+        self.arcs[2 * i + 1].1
+    }
+    // dual variables; the reduced cost cost(u, v) + potential(u) - potential(v) of every
+    // edge with residual capacity is non-negative
+    pub fn potential(&self, v: usize) -> D {
+        self.pi[v]
+    }
+    // the minimum cost of a flow meeting the supplies, if any; each call starts from zero flow
+    pub fn solve(&mut self) -> Option<D> {
+        let n = self.n;
+        let zero = D::default();
+        let m = self.arcs.len();
+        for a in self.arcs.chunks_mut(2) {
+            a[0].1 = a[0].1 + a[1].1;
+            a[1].1 = zero;
+        }
+        let mut arcs = self.arcs.clone();
+        let one = D::from(true);
+        // the artificial edges cost more than any simple path
+        let big = arcs
+            .iter()
+            .map(|a| if a.2 < zero { -a.2 } else { a.2 })
+            .fold(one, |s, c| s + c);
+        let mut total = zero;
+        for &b in &self.supply {
+            if b > zero {
+                total = total + b;
+            }
+        }
+        let root = n;
+        let mut par = vec![root; n + 1];
+        // the tree arc from par[v] to v
+        let mut up = vec![!0; n + 1];
+        let mut children = vec![vec![]; n + 1];
+        let mut pi = vec![zero; n + 1];
+        let mut depth = vec![1; n + 1];
+        depth[root] = 0;
+        for v in 0..n {
+            let b = self.supply[v];
+            // edges with no flow are directed away from the root
+            let (from, to, flow) = if b > zero {
+                (v, root, b)
+            } else {
+                (root, v, -b)
+            };
+            let a = arcs.len();
+            arcs.push((to, total - flow, big));
+            arcs.push((from, flow, -big));
+            up[v] = if b > zero { a + 1 } else { a };
+            pi[v] = arcs[up[v]].2;
+            children[root].push(v);
+        }
+        let reduced = |arcs: &[(usize, D, D)], pi: &[D], a: usize| {
+            arcs[a].2 + pi[arcs[a ^ 1].0] - pi[arcs[a].0]
+        };
+        let total_arcs = arcs.len();
+        let block = ((total_arcs as f64).sqrt() as usize).max(10);
+        let mut next = 0;
+        loop {
+            // block search for the entering arc
+            let mut enter = None;
+            let mut seen = 0;
+            while seen < total_arcs {
+                let mut best = zero;
+                for _ in 0..block.min(total_arcs - seen) {
+                    let a = next;
+                    next = if next + 1 == total_arcs { 0 } else { next + 1 };
+                    seen += 1;
+                    if arcs[a].1 > zero {
+                        let r = reduced(&arcs, &pi, a);
+                        if r < best {
+                            best = r;
+                            enter = Some(a);
+                        }
+                    }
+                }
+                if enter.is_some() {
+                    break;
+                }
+            }
+            let a = match enter {
+                Some(a) => a,
+                None => break,
+            };
+            let (u, w) = (arcs[a ^ 1].0, arcs[a].0);
+            // the cycle from the apex down to u, along a, and from w up to the apex
+            let (mut x, mut y) = (u, w);
+            let mut down = vec![];
+            let mut upward = vec![];
+            while x != y {
+                if depth[x] >= depth[y] {
+                    down.push(up[x]);
+                    x = par[x];
+                } else {
+                    upward.push(up[y] ^ 1);
+                    y = par[y];
+                }
+            }
+            down.reverse();
+            let cycle: Vec<usize> = down
+                .iter()
+                .copied()
+                .chain(Some(a))
+                .chain(upward.iter().copied())
+                .collect();
+            let mut delta = arcs[a].1;
+            let mut leave = a;
+            for &b in &cycle {
+                if arcs[b].1 <= delta {
+                    delta = arcs[b].1;
+                    leave = b;
+                }
+            }
+            for &b in &cycle {
+                arcs[b].1 = arcs[b].1 - delta;
+                arcs[b ^ 1].1 = arcs[b ^ 1].1 + delta;
+            }
+            if leave == a {
+                continue;
+            }
+            // the leaving arc joins y to its parent; the side of the cycle it lies on is
+            // detached and hangs again from the entering arc
+            let on_down = down.contains(&leave);
+            let y = if on_down {
+                arcs[leave].0
+            } else {
+                arcs[leave ^ 1].0
+            };
+            let (mut v, mut p, mut pa) = if on_down { (u, w, a ^ 1) } else { (w, u, a) };
+            loop {
+                let (old_par, old_up) = (par[v], up[v]);
+                let c = &mut children[old_par];
+                c.swap_remove(c.iter().position(|&c| c == v).unwrap());
+                par[v] = p;
+                up[v] = pa;
+                children[p].push(v);
+                if v == y {
+                    break;
+                }
+                p = v;
+                pa = old_up ^ 1;
+                v = old_par;
+            }
+            let r = if on_down { u } else { w };
+            let mut stk = vec![r];
+            while let Some(v) = stk.pop() {
+                depth[v] = depth[par[v]] + 1;
+                pi[v] = pi[par[v]] + arcs[up[v]].2;
+                stk.extend(children[v].iter().copied());
+            }
+        }
+        if arcs[m..].chunks(2).any(|a| a[1].1 > zero) {
+            return None;
+        }
+        arcs.truncate(m);
+        self.arcs = arcs;
+        self.pi = pi[..n].to_vec();
+        let cost = (0..m / 2).fold(zero, |s, i| s + self.arcs[2 * i + 1].1 * self.arcs[2 * i].2);
+        Some(cost)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::min_cost_flow::MinCostFlow;
+    use crate::random::*;
+
+    #[test]
+    fn network_simplex_twice() {
+        let mut ns = NetworkSimplex::new(2);
+        ns.add_edge(0, 1, 5, 3);
+        ns.add_supply(0, 2);
+        ns.add_supply(1, -2);
+        assert_eq!(ns.solve(), Some(6));
+        assert_eq!(ns.solve(), Some(6));
+        assert_eq!(ns.flow(0), 2);
+        ns.add_supply(0, 4);
+        ns.add_supply(1, -4);
+        assert_eq!(ns.solve(), None);
+        ns.add_edge(0, 1, 1, 5);
+        assert_eq!(ns.solve(), Some(20));
+        assert_eq!((ns.flow(0), ns.flow(1)), (5, 1));
+    }
+
+    #[test]
+    fn network_simplex_supplies() {
+        let mut rand = Pcg::seed_from_u64(4902);
+        for _ in 0..20 {
+            let n = 100;
+            let edges: Vec<_> = (0..1000)
+                .map(|_| {
+                    let u = rand.next_u32() as usize % n;
+                    let v = rand.next_u32() as usize % n;
+                    let c = (rand.next_u32() % 20) as i64;
+                    (u, v, c, (rand.next_u32() % 100) as i64)
+                })
+                .collect();
+            let mut supply: Vec<_> = (0..n).map(|_| (rand.next_u32() % 11) as i64 - 5).collect();
+            let sum: i64 = supply.iter().sum();
+            supply[0] -= sum;
+            // compare with min cost flow through a super source and sink
+            let (s, t) = (n, n + 1);
+            let mut mcf = MinCostFlow::<i64, i64>::with_types(n + 2);
+            let mut ns = NetworkSimplex::new(n);
+            for &(u, v, c, d) in &edges {
+                mcf.add_edge(u, v, c, d);
+                ns.add_edge(u, v, c, d);
+            }
+            let mut need = 0;
+            for (v, &b) in supply.iter().enumerate() {
+                if b > 0 {
+                    mcf.add_edge(s, v, b, 0);
+                    need += b;
+                } else if b < 0 {
+                    mcf.add_edge(v, t, -b, 0);
+                }
+                ns.add_supply(v, b);
+            }
+            let (f, cost) = mcf.run(s, t, i64::MAX);
+            let res = ns.solve();
+            if f < need {
+                assert_eq!(res, None);
+                continue;
+            }
+            assert_eq!(res, Some(cost));
+            let mut bal = supply.clone();
+            for (i, &(u, v, c, d)) in edges.iter().enumerate() {
+                let x = ns.flow(i);
+                assert!(0 <= x && x <= c);
+                bal[u] -= x;
+                bal[v] += x;
+                // reduced costs are consistent with the flow
+                let r = d + ns.potential(u) - ns.potential(v);
+                assert!(r >= 0 || x == c);
+                assert!(r <= 0 || x == 0);
+            }
+            assert!(bal.iter().all(|&b| b == 0));
+        }
+    }
+}
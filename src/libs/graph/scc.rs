@@ -1,4 +1,8 @@
-pub fn strongly_connected_components(g: &[Vec<usize>]) -> (usize, Vec<usize>) {
+use crate::graph2::Graph;
+
+pub fn strongly_connected_components<'a, T: 'a, G: Graph<'a, T> + ?Sized>(
+    g: &'a G,
+) -> (usize, Vec<usize>) {
     let n = g.len();
     Scc {
         g,
@@ -10,19 +14,49 @@ pub fn strongly_connected_components(g: &[Vec<usize>]) -> (usize, Vec<usize>) {
     }
     .run()
 }
-struct Scc<'a> {
-    g: &'a [Vec<usize>],
+// the graph of the strongly connected components, numbered in topological order
+#[derive(Clone, Debug)]
+pub struct Condensation {
+    // the component of each vertex
+    pub comp: Vec<usize>,
+    pub members: Vec<Vec<usize>>,
+    // the edges between the components without duplicates
+    pub dag: Vec<Vec<usize>>,
+}
+pub fn condensation<'a, T: 'a, G: Graph<'a, T> + ?Sized>(g: &'a G) -> Condensation {
+    let (k, comp) = strongly_connected_components(g);
+    let mut members = vec![vec![]; k];
+    let mut dag = vec![vec![]; k];
+    for u in 0..g.len() {
+        members[comp[u]].push(u);
+        for v in g.adj_unlabeled(u) {
+            if comp[u] != comp[v] {
+                dag[comp[u]].push(comp[v]);
+            }
+        }
+    }
+    for a in &mut dag {
+        a.sort_unstable();
+        a.dedup();
+    }
+    Condensation { comp, members, dag }
+}
+struct Scc<'a, G: ?Sized> {
+    g: &'a G,
     stk: Vec<usize>,
     ord: Vec<usize>,
     low: Vec<usize>,
     idx: usize,
     comp_id: usize,
 }
-impl<'a> Scc<'a> {
-    fn run(mut self) -> (usize, Vec<usize>) {
+impl<'a, G: ?Sized> Scc<'a, G> {
+    fn run<T: 'a>(mut self) -> (usize, Vec<usize>)
+    where
+        G: Graph<'a, T>,
+    {
         for r in 0..self.g.len() {
             if self.ord[r] == 0 {
-                self.rec(r);
+                self.dfs(r);
             }
         }
         for c in &mut self.ord {
@@ -30,25 +64,110 @@ impl<'a> Scc<'a> {
         }
         (self.comp_id, self.ord)
     }
-    fn rec(&mut self, u: usize) {
+    fn enter(&mut self, u: usize) {
         self.ord[u] = self.idx;
         self.low[u] = self.idx;
         self.idx += 1;
         self.stk.push(u);
-        for &v in &self.g[u] {
-            if self.ord[v] == 0 {
-                self.rec(v);
-                self.low[u] = self.low[u].min(self.low[v]);
-            } else {
-                self.low[u] = self.low[u].min(self.ord[v]);
+    }
+    fn dfs<T: 'a>(&mut self, r: usize)
+    where
+        G: Graph<'a, T>,
+    {
+        self.enter(r);
+        let mut call = vec![(r, self.g.adj_unlabeled(r))];
+        while let Some((u, it)) = call.last_mut() {
+            let u = *u;
+            if let Some(v) = it.next() {
+                if self.ord[v] == 0 {
+                    self.enter(v);
+                    call.push((v, self.g.adj_unlabeled(v)));
+                } else {
+                    self.low[u] = self.low[u].min(self.ord[v]);
+                }
+                continue;
+            }
+            call.pop();
+            if let Some(&(p, _)) = call.last() {
+                self.low[p] = self.low[p].min(self.low[u]);
+            }
+            if self.ord[u] == self.low[u] {
+                let i = self.stk.iter().rposition(|v| *v == u).unwrap();
+                self.comp_id += 1;
+                for v in self.stk.drain(i..) {
+                    self.ord[v] = -(self.comp_id as isize) as usize;
+                }
             }
         }
-        if self.ord[u] == self.low[u] {
-            let i = self.stk.iter().rposition(|v| *v == u).unwrap();
-            self.comp_id += 1;
-            for v in self.stk.drain(i..) {
-                self.ord[v] = -(self.comp_id as isize) as usize;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::*;
+
+    #[test]
+    fn scc_random() {
+        let mut rand = Pcg::seed_from_u64(4201);
+        for _ in 0..50 {
+            let n = rand.next_u32() as usize % 10 + 1;
+            let mut g = vec![vec![]; n];
+            let mut reach = vec![vec![false; n]; n];
+            for (u, r) in reach.iter_mut().enumerate() {
+                r[u] = true;
+            }
+            for _ in 0..rand.next_u32() % 20 {
+                let u = rand.next_u32() as usize % n;
+                let v = rand.next_u32() as usize % n;
+                g[u].push(v);
+                reach[u][v] = true;
+            }
+            for k in 0..n {
+                for i in 0..n {
+                    for j in 0..n {
+                        reach[i][j] |= reach[i][k] && reach[k][j];
+                    }
+                }
+            }
+            let (cnt, comp) = strongly_connected_components(&g);
+            assert!(comp.iter().all(|&c| c < cnt));
+            for u in 0..n {
+                for v in 0..n {
+                    assert_eq!(comp[u] == comp[v], reach[u][v] && reach[v][u]);
+                    if reach[u][v] {
+                        assert!(comp[u] <= comp[v]);
+                    }
+                }
             }
         }
     }
+
+    #[test]
+    fn scc_condensation() {
+        let g = vec![
+            vec![1],
+            vec![2, 3],
+            vec![0, 3],
+            vec![4, 4],
+            vec![3],
+            vec![4],
+        ];
+        let c = condensation(&g);
+        assert_eq!(c.members, [vec![5], vec![0, 1, 2], vec![3, 4]]);
+        assert_eq!(c.comp, [1, 1, 1, 2, 2, 0]);
+        assert_eq!(c.dag, [vec![2], vec![2], vec![]]);
+    }
+
+    #[test]
+    fn scc_long_path() {
+        let n = 1_000_000;
+        let mut g: Vec<_> = (0..n).map(|u| vec![u + 1]).collect();
+        g[n - 1].clear();
+        let (cnt, comp) = strongly_connected_components(&g);
+        assert_eq!(cnt, n);
+        assert!(comp.iter().enumerate().all(|(u, &c)| c == u));
+        g[n - 1].push(0);
+        assert_eq!(strongly_connected_components(&g).0, 1);
+    }
 }
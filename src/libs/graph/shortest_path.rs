@@ -0,0 +1,351 @@
+use super::djikstra::djikstra;
+use crate::graph2::{AdjList, Graph};
+use crate::other::matrix::Matrix;
+use std::collections::VecDeque;
+use std::ops::{Add, Sub};
+
+// the distances with a shortest path tree
+#[derive(Clone, Debug)]
+pub struct ShortestPaths<T> {
+    pub dist: Vec<Option<T>>,
+    // the previous vertex on a shortest path; !0 for the sources and unreachable vertices
+    pub par: Vec<usize>,
+}
+impl<T> ShortestPaths<T> {
+    pub fn new(n: usize) -> Self {
+        Self {
+            dist: std::iter::repeat_with(|| None).take(n).collect(),
+            par: vec![!0; n],
+        }
+    }
+    // the distances with inf for the unreachable vertices
+    pub fn dist_or(&self, inf: T) -> Vec<T>
+    where
+        T: Copy,
+    {
+        self.dist.iter().map(|d| d.unwrap_or(inf)).collect()
+    }
+    // the vertices from a source to t
+    pub fn path_to(&self, t: usize) -> Option<Vec<usize>> {
+        self.dist[t].as_ref()?;
+        let mut res = vec![t];
+        let mut u = t;
+        while self.par[u] != !0 {
+            u = self.par[u];
+            res.push(u);
+        }
+        res.reverse();
+        Some(res)
+    }
+}
+
+// the weights are 0 or 1
+pub fn zero_one_bfs<'a, G: Graph<'a, usize> + ?Sized>(g: &'a G, s: usize) -> ShortestPaths<usize> {
+    let mut res = ShortestPaths::new(g.len());
+    res.dist[s] = Some(0);
+    let mut que = VecDeque::new();
+    que.push_back((s, 0));
+    while let Some((u, d)) = que.pop_front() {
+        if res.dist[u] != Some(d) {
+            continue;
+        }
+        for (v, &w) in g.adj(u) {
+            assert!(w <= 1);
+            if res.dist[v].map(|dv| d + w < dv).unwrap_or(true) {
+                res.dist[v] = Some(d + w);
+                res.par[v] = u;
+                if w == 0 {
+                    que.push_front((v, d));
+                } else {
+                    que.push_back((v, d + 1));
+                }
+            }
+        }
+    }
+    res
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Dist<T> {
+    // reachable through a negative cycle
+    NegInf,
+    Finite(T),
+    Unreachable,
+}
+
+pub fn bellman_ford<'a, T, G>(g: &'a G, s: usize) -> Vec<Dist<T>>
+where
+    G: Graph<'a, T> + ?Sized,
+    T: 'a + Add<Output = T> + Ord + Default + Copy,
+{
+    let n = g.len();
+    let mut dist = vec![None; n];
+    let mut neg = vec![false; n];
+    dist[s] = Some(T::default());
+    // after n - 1 rounds, the vertices still improved are behind negative cycles, which
+    // spread to everything reachable in n more rounds
+    for i in 0..2 * n {
+        let mut updated = false;
+        for u in 0..n {
+            let du = match dist[u] {
+                Some(du) => du,
+                None => continue,
+            };
+            for (v, &w) in g.adj(u) {
+                if neg[u] && !neg[v] {
+                    neg[v] = true;
+                    updated = true;
+                }
+                if dist[v].map(|dv| du + w < dv).unwrap_or(true) {
+                    dist[v] = Some(du + w);
+                    updated = true;
+                    if i + 1 >= n {
+                        neg[v] = true;
+                    }
+                }
+            }
+        }
+        if !updated {
+            break;
+        }
+    }
+    dist.into_iter()
+        .zip(neg)
+        .map(|(d, neg)| match d {
+            _ if neg => Dist::NegInf,
+            Some(d) => Dist::Finite(d),
+            None => Dist::Unreachable,
+        })
+        .collect()
+}
+
+// d holds the weights of the edges, None for no edge, and becomes the distances;
+// false if there is a negative cycle, which is detected as soon as it closes so that the
+// values do not blow up, and then d is left partially updated
+pub fn floyd_warshall<T>(d: &mut Matrix<Option<T>>) -> bool
+where
+    T: Add<Output = T> + Ord + Default + Copy,
+{
+    assert!(d.is_square());
+    let n = d.len();
+    for i in 0..n {
+        d[i][i] = Some(d[i][i].map_or(T::default(), |x| x.min(T::default())));
+    }
+    for k in 0..n {
+        for i in 0..n {
+            let dik = match d[i][k] {
+                Some(x) => x,
+                None => continue,
+            };
+            for j in 0..n {
+                if let Some(dkj) = d[k][j] {
+                    if d[i][j].map(|dij| dik + dkj < dij).unwrap_or(true) {
+                        d[i][j] = Some(dik + dkj);
+                    }
+                }
+            }
+        }
+        if (0..n).any(|i| d[i][i] < Some(T::default())) {
+            return false;
+        }
+    }
+    true
+}
+
+// all-pairs distances by Dijkstra after reweighting with potentials; None if there is a
+// negative cycle
+pub fn johnson<'a, T, G>(g: &'a G) -> Option<Matrix<Option<T>>>
+where
+    G: Graph<'a, T> + ?Sized,
+    T: 'a + Add<Output = T> + Sub<Output = T> + Ord + Default + Copy,
+{
+    let n = g.len();
+    // the distances from a virtual source with edges of weight 0 to every vertex
+    let mut h = vec![T::default(); n];
+    for i in 0..=n {
+        let mut updated = false;
+        for u in 0..n {
+            for (v, &w) in g.adj(u) {
+                if h[u] + w < h[v] {
+                    h[v] = h[u] + w;
+                    updated = true;
+                }
+            }
+        }
+        if !updated {
+            break;
+        }
+        if i == n {
+            return None;
+        }
+    }
+    let mut b = AdjList::builder(n);
+    for u in 0..n {
+        for (v, &w) in g.adj(u) {
+            b.labeled_edge(u, v, h[u] + w - h[v]);
+        }
+    }
+    let rg = b.build();
+    let mut res = Matrix::repeat(n, n, None);
+    for s in 0..n {
+        for (t, d) in djikstra(&rg, s).into_iter().enumerate() {
+            res[s][t] = d.map(|d| d + h[t] - h[s]);
+        }
+    }
+    Some(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::djikstra::{djikstra_multi, djikstra_with_parents};
+    use crate::random::*;
+
+    fn random_graph(rand: &mut Pcg, n: usize, m: usize, w: i64) -> Vec<(usize, usize, i64)> {
+        (0..m)
+            .map(|_| {
+                let u = rand.next_u32() as usize % n;
+                let v = rand.next_u32() as usize % n;
+                (u, v, (rand.next_u32() % 20) as i64 - w)
+            })
+            .collect()
+    }
+    // the least weight of the path along the edges
+    fn path_weight(edges: &[(usize, usize, i64)], path: &[usize]) -> i64 {
+        path.windows(2)
+            .map(|p| {
+                edges
+                    .iter()
+                    .filter(|e| (e.0, e.1) == (p[0], p[1]))
+                    .map(|e| e.2)
+                    .min()
+                    .unwrap()
+            })
+            .sum()
+    }
+
+    #[test]
+    fn djikstra_paths() {
+        let mut rand = Pcg::seed_from_u64(4601);
+        for _ in 0..50 {
+            let n = rand.next_u32() as usize % 10 + 1;
+            let edges = random_graph(&mut rand, n, 25, 0);
+            let g = AdjList::from_labeled_edges(n, edges.iter().copied());
+            let mut d = Matrix::repeat(n, n, None);
+            for &(u, v, w) in &edges {
+                d[u][v] = Some(d[u][v].map_or(w, |x: i64| x.min(w)));
+            }
+            assert!(floyd_warshall(&mut d));
+            assert_eq!(johnson(&g), Some(d.clone()));
+            for s in 0..n {
+                let sp = djikstra_with_parents(&g, s);
+                assert_eq!(sp.dist, d[s]);
+                for t in 0..n {
+                    let path = sp.path_to(t);
+                    assert_eq!(path.is_some(), d[s][t].is_some());
+                    if let Some(path) = path {
+                        assert_eq!((path[0], path[path.len() - 1]), (s, t));
+                        assert_eq!(Some(path_weight(&edges, &path)), d[s][t]);
+                    }
+                }
+                let bf = bellman_ford(&g, s);
+                let expected: Vec<_> = d[s]
+                    .iter()
+                    .map(|x| x.map_or(Dist::Unreachable, Dist::Finite))
+                    .collect();
+                assert_eq!(bf, expected);
+            }
+            let sources = [0, n - 1, n / 2];
+            let sp = djikstra_multi(&g, sources.iter().copied());
+            for t in 0..n {
+                let e = sources.iter().filter_map(|&s| d[s][t]).min();
+                assert_eq!(sp.dist[t], e);
+                if let Some(path) = sp.path_to(t) {
+                    assert!(sources.contains(&path[0]));
+                    assert_eq!(Some(path_weight(&edges, &path)), e);
+                }
+            }
+
+            let g01 = AdjList::from_labeled_edges(
+                n,
+                edges.iter().map(|&(u, v, w)| (u, v, (w & 1) as usize)),
+            );
+            let s = rand.next_u32() as usize % n;
+            let sp = zero_one_bfs(&g01, s);
+            assert_eq!(sp.dist, djikstra(&g01, s));
+            for t in 0..n {
+                if let Some(path) = sp.path_to(t) {
+                    let w: usize = path
+                        .windows(2)
+                        .map(|p| {
+                            g01[p[0]]
+                                .iter()
+                                .filter(|e| e.0 == p[1])
+                                .map(|e| e.1)
+                                .min()
+                                .unwrap()
+                        })
+                        .sum();
+                    assert_eq!(Some(w), sp.dist[t]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn floyd_warshall_overflow() {
+        let n = 80;
+        let mut d = Matrix::repeat(n, n, Some(-1i64));
+        assert!(!floyd_warshall(&mut d));
+    }
+
+    #[test]
+    fn negative_cycles() {
+        let mut rand = Pcg::seed_from_u64(4602);
+        for _ in 0..100 {
+            let n = rand.next_u32() as usize % 8 + 1;
+            let m = rand.next_u32() as usize % 15;
+            let edges = random_graph(&mut rand, n, m, 4);
+            let g = AdjList::from_labeled_edges(n, edges.iter().copied());
+            let mut d = Matrix::repeat(n, n, None);
+            for &(u, v, w) in &edges {
+                d[u][v] = Some(d[u][v].map_or(w, |x: i64| x.min(w)));
+            }
+            let mut fw = d.clone();
+            let ok = floyd_warshall(&mut fw);
+            // every round in full; the values stay small for small n
+            for i in 0..n {
+                d[i][i] = Some(d[i][i].map_or(0, |x| x.min(0)));
+            }
+            for k in 0..n {
+                for i in 0..n {
+                    for j in 0..n {
+                        if let (Some(x), Some(y)) = (d[i][k], d[k][j]) {
+                            d[i][j] = Some(d[i][j].map_or(x + y, |z| z.min(x + y)));
+                        }
+                    }
+                }
+            }
+            assert_eq!(ok, (0..n).all(|i| d[i][i] == Some(0)));
+            if ok {
+                assert_eq!(fw, d);
+            }
+            assert_eq!(johnson(&g), Some(d.clone()).filter(|_| ok));
+            let on_cycle: Vec<_> = (0..n).filter(|&c| d[c][c] < Some(0)).collect();
+            for s in 0..n {
+                let bf = bellman_ford(&g, s);
+                for t in 0..n {
+                    let neg = on_cycle
+                        .iter()
+                        .any(|&c| d[s][c].is_some() && d[c][t].is_some());
+                    let e = match d[s][t] {
+                        _ if neg => Dist::NegInf,
+                        Some(x) => Dist::Finite(x),
+                        None => Dist::Unreachable,
+                    };
+                    assert_eq!(bf[t], e);
+                }
+            }
+        }
+    }
+}
@@ -1,6 +1,6 @@
-use super::Graph;
+use crate::graph2::Graph;
 
-pub fn tsort(g: &Graph) -> Option<Vec<usize>> {
+pub fn tsort<'a, T: 'a, G: Graph<'a, T> + ?Sized>(g: &'a G) -> Option<Vec<usize>> {
     let mut res = vec![0; g.len()];
     let mut i = res.len();
     let mut stack = Vec::new();
@@ -19,13 +19,15 @@ pub fn tsort(g: &Graph) -> Option<Vec<usize>> {
                 }
                 state[u] = 1;
                 stack.push(!u);
-                for &v in g[u].iter().rev() {
+                let k = stack.len();
+                for v in g.adj_unlabeled(u) {
                     match state[v] {
                         0 => stack.push(v),
                         1 => return None,
                         _ => {}
                     }
                 }
+                stack[k..].reverse();
             } else {
                 let u = !u;
                 state[u] = 2;
@@ -0,0 +1,78 @@
+use super::scc::strongly_connected_components;
+
+// the literal (x_i = f) is the vertex 2i + f
+pub struct TwoSat {
+    g: Vec<Vec<usize>>,
+    answer: Vec<bool>,
+}
+impl TwoSat {
+    pub fn new(n: usize) -> Self {
+        Self {
+            g: vec![vec![]; 2 * n],
+            answer: vec![false; n],
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.answer.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.answer.is_empty()
+    }
+    // (x_i = f) or (x_j = g)
+    pub fn add_clause(&mut self, i: usize, f: bool, j: usize, g: bool) {
+        assert!(i < self.len() && j < self.len());
+        let (a, b) = (2 * i + f as usize, 2 * j + g as usize);
+        self.g[a ^ 1].push(b);
+        self.g[b ^ 1].push(a);
+    }
+    // finds an assignment, which answer returns afterwards
+    pub fn satisfiable(&mut self) -> bool {
+        // the components are numbered in topological order, as in condensation
+        let (_, comp) = strongly_connected_components(&self.g);
+        for (i, x) in self.answer.iter_mut().enumerate() {
+            if comp[2 * i] == comp[2 * i + 1] {
+                return false;
+            }
+            // the later literal in topological order cannot imply the other
+            *x = comp[2 * i] < comp[2 * i + 1];
+        }
+        true
+    }
+    pub fn answer(&self) -> &[bool] {
+        &self.answer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::*;
+
+    #[test]
+    fn two_sat_random() {
+        let mut rand = Pcg::seed_from_u64(4401);
+        for _ in 0..200 {
+            let n = rand.next_u32() as usize % 6 + 1;
+            let clauses: Vec<_> = (0..rand.next_u32() % 12)
+                .map(|_| {
+                    let i = rand.next_u32() as usize % n;
+                    let j = rand.next_u32() as usize % n;
+                    (i, rand.next_u32() & 1 == 1, j, rand.next_u32() & 1 == 1)
+                })
+                .collect();
+            let ok = |x: &[bool]| clauses.iter().all(|&(i, f, j, g)| x[i] == f || x[j] == g);
+            let mut ts = TwoSat::new(n);
+            for &(i, f, j, g) in &clauses {
+                ts.add_clause(i, f, j, g);
+            }
+            let exists = (0..1 << n).any(|s: usize| {
+                let x: Vec<_> = (0..n).map(|i| s >> i & 1 == 1).collect();
+                ok(&x)
+            });
+            assert_eq!(ts.satisfiable(), exists);
+            if exists {
+                assert!(ok(ts.answer()));
+            }
+        }
+    }
+}
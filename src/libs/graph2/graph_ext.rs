@@ -1,38 +1,14 @@
+use super::Graph;
+use std::collections::{BinaryHeap, VecDeque};
+
 pub trait GraphExt<'a, T: 'a>: Graph<'a, T> {
     fn tsort(&'a self) -> Option<Vec<usize>> {
-        let mut res = Vec::with_capacity(self.len());
-        let mut state = vec![0u8; self.len()];
-        let mut stk = vec![];
-        for s in (0..self.len()).rev() {
-            if state[s] != 0 {
-                debug_assert_ne!(state[s], 1);
-                continue;
-            }
-            state[s] = 1;
-            stk.push((s, self.adj(s)));
-            while let Some((u, adj)) = stk.last_mut() {
-                if let Some((v, _)) = adj.next() {
-                    match state[v] {
-                        1 => return None,
-                        2 => continue,
-                        _ => {}
-                    }
-                    state[v] = 1;
-                    stk.push((v, self.adj(v)));
-                } else {
-                    state[*u] = 2;
-                    res.push(*u);
-                    stk.pop();
-                }
-            }
-        }
-        res.reverse();
-        Some(res)
+        crate::graph::tsort::tsort(self)
     }
     fn djikstra(&'a self, s: usize, init: T) -> Vec<Option<T>>
     where
         T: Clone + Ord,
-        for <'b> &'b T: std::ops::Add<&'b T, Output = T>
+        for<'b> &'b T: std::ops::Add<&'b T, Output = T>,
     {
         use std::cmp::Reverse;
         let mut res: Vec<_> = (0..self.len()).map(|_| None).collect();
@@ -43,7 +19,7 @@ pub trait GraphExt<'a, T: 'a>: Graph<'a, T> {
                 continue;
             }
             for (v, w) in self.adj(u) {
-                let dsuv = &dsu + &w;
+                let dsuv = &dsu + w;
                 if res[v].as_ref().map(|d| &dsuv < d).unwrap_or(true) {
                     res[v] = Some(dsuv.clone());
                     que.push((Reverse(dsuv), v));
@@ -69,4 +45,4 @@ pub trait GraphExt<'a, T: 'a>: Graph<'a, T> {
         res
     }
 }
-impl<'a, T: 'a, G: Graph<'a, T>> GraphExt<'a, T> for G {}
\ No newline at end of file
+impl<'a, T: 'a, G: Graph<'a, T>> GraphExt<'a, T> for G {}
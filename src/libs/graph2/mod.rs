@@ -1,3 +1,11 @@
+mod graph_ext;
+mod tree;
+pub use graph_ext::GraphExt;
+pub use tree::Tree;
+
+use crate::graph::{self, wip, LabeledGraph};
+
+// adjacency of a graph whose edges are labeled with T; unlabeled graphs use T = ()
 pub trait Graph<'a, T: 'a> {
     type Adj: Iterator<Item = (usize, &'a T)>;
     fn adj(&'a self, u: usize) -> Self::Adj;
@@ -5,6 +13,9 @@ pub trait Graph<'a, T: 'a> {
         AdjUnlabeled(self.adj(u))
     }
     fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
 pub struct AdjUnlabeled<I>(I);
 impl<T, I: Iterator<Item = (usize, T)>> Iterator for AdjUnlabeled<I> {
@@ -29,20 +40,23 @@ impl<T> AdjList<T> {
     pub fn edge_count(&self) -> usize {
         self.edges.len()
     }
-    pub fn iter(&self) -> Iter<T> {
-        Iter {
-            g: self,
-            u: 0,
-        }
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { g: self, u: 0 }
     }
-    pub fn from_labeled_edges<I: IntoIterator<Item = (usize, usize, T)>>(n: usize, iter: I) -> Self {
+    pub fn from_labeled_edges<I: IntoIterator<Item = (usize, usize, T)>>(
+        n: usize,
+        iter: I,
+    ) -> Self {
         let mut b = Self::builder(n);
         b.extend_labeled(iter);
         b.build()
     }
-    pub fn from_labeled_bi_edges<I: IntoIterator<Item = (usize, usize, T)>>(n: usize, iter: I) -> Self
+    pub fn from_labeled_bi_edges<I: IntoIterator<Item = (usize, usize, T)>>(
+        n: usize,
+        iter: I,
+    ) -> Self
     where
-        T: Clone
+        T: Clone,
     {
         let mut b = Self::builder(n);
         b.extend_labeled_bi_edges(iter);
@@ -70,7 +84,9 @@ impl<T> std::ops::IndexMut<usize> for AdjList<T> {
 }
 impl<T: std::fmt::Debug> std::fmt::Debug for AdjList<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_map().entries((0..self.len()).map(|u| (u, &self[u]))).finish()
+        f.debug_map()
+            .entries((0..self.len()).map(|u| (u, &self[u])))
+            .finish()
     }
 }
 impl<'a, T: 'a> Graph<'a, T> for AdjList<T> {
@@ -82,6 +98,21 @@ impl<'a, T: 'a> Graph<'a, T> for AdjList<T> {
         self.heads.len() - 1
     }
 }
+pub struct Iter<'a, T> {
+    g: &'a AdjList<T>,
+    u: usize,
+}
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a [(usize, T)];
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.u < self.g.len() {
+            self.u += 1;
+            Some(&self.g[self.u - 1])
+        } else {
+            None
+        }
+    }
+}
 pub struct AdjIter<'a, T>(std::slice::Iter<'a, (usize, T)>);
 impl<'a, T> Iterator for AdjIter<'a, T> {
     type Item = (usize, &'a T);
@@ -116,15 +147,9 @@ impl<T> AdjListBuilder<T> {
         self.heads.push(edges.len());
         AdjList {
             heads: self.heads,
-            edges
+            edges,
         }
     }
-    pub fn edge(&mut self, u: usize, v: usize) {
-        self.labeled_edge(u, v, ());
-    }
-    pub fn bi_edge(&mut self, u: usize, v: usize) {
-        self.labeled_bi_edge(u, v, ());
-    }
     pub fn labeled_edge(&mut self, u: usize, v: usize, x: T) {
         assert!(u < self.heads.len());
         self.edges.push((v, x));
@@ -135,12 +160,12 @@ impl<T> AdjListBuilder<T> {
     where
         T: Clone,
     {
-        self.edge(u, v, x.clone());
-        self.edge(v, u, x);
+        self.labeled_edge(u, v, x.clone());
+        self.labeled_edge(v, u, x);
     }
     pub fn extend_labeled<I: IntoIterator<Item = (usize, usize, T)>>(&mut self, iter: I) {
         for (u, v, x) in iter {
-            self.edge(u, v, x);
+            self.labeled_edge(u, v, x);
         }
     }
     pub fn extend_labeled_bi_edges<I: IntoIterator<Item = (usize, usize, T)>>(&mut self, iter: I)
@@ -148,11 +173,17 @@ impl<T> AdjListBuilder<T> {
         T: Clone,
     {
         for (u, v, x) in iter {
-            self.bi_edge(u, v, x);
+            self.labeled_bi_edge(u, v, x);
         }
     }
 }
 impl AdjListBuilder<()> {
+    pub fn edge(&mut self, u: usize, v: usize) {
+        self.labeled_edge(u, v, ());
+    }
+    pub fn bi_edge(&mut self, u: usize, v: usize) {
+        self.labeled_bi_edge(u, v, ());
+    }
     pub fn extend_bi_edges<I: IntoIterator<Item = (usize, usize)>>(&mut self, iter: I) {
         self.extend_labeled_bi_edges(iter.into_iter().map(|(u, v)| (u, v, ())));
     }
@@ -162,3 +193,130 @@ impl std::iter::Extend<(usize, usize)> for AdjListBuilder<()> {
         self.extend_labeled(iter.into_iter().map(|(u, v)| (u, v, ())));
     }
 }
+
+pub struct UnlabeledAdj<'a>(std::slice::Iter<'a, usize>);
+impl<'a> Iterator for UnlabeledAdj<'a> {
+    type Item = (usize, &'a ());
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|&v| (v, &()))
+    }
+}
+impl<'a> Graph<'a, ()> for [Vec<usize>] {
+    type Adj = UnlabeledAdj<'a>;
+    fn adj(&'a self, u: usize) -> Self::Adj {
+        UnlabeledAdj(self[u].iter())
+    }
+    fn len(&self) -> usize {
+        <[_]>::len(self)
+    }
+}
+impl<'a> Graph<'a, ()> for Vec<Vec<usize>> {
+    type Adj = UnlabeledAdj<'a>;
+    fn adj(&'a self, u: usize) -> Self::Adj {
+        UnlabeledAdj(self[u].iter())
+    }
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+}
+impl<'a> Graph<'a, ()> for graph::Graph {
+    type Adj = UnlabeledAdj<'a>;
+    fn adj(&'a self, u: usize) -> Self::Adj {
+        UnlabeledAdj(self[u].iter())
+    }
+    fn len(&self) -> usize {
+        graph::Graph::len(self)
+    }
+}
+impl<'a> Graph<'a, ()> for wip::AdjList {
+    type Adj = UnlabeledAdj<'a>;
+    fn adj(&'a self, u: usize) -> Self::Adj {
+        UnlabeledAdj(self[u].iter())
+    }
+    fn len(&self) -> usize {
+        wip::AdjList::len(self)
+    }
+}
+impl<'a, T: 'a> Graph<'a, T> for LabeledGraph<T> {
+    type Adj = AdjIter<'a, T>;
+    fn adj(&'a self, u: usize) -> Self::Adj {
+        AdjIter(self[u].iter())
+    }
+    fn len(&self) -> usize {
+        LabeledGraph::len(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{djikstra::djikstra, scc::strongly_connected_components, tsort::tsort};
+    use crate::random::*;
+
+    // the same results regardless of the representation
+    fn check<'a, G: Graph<'a, ()> + ?Sized>(
+        g: &'a G,
+        edges: &[(usize, usize)],
+        expected: &[usize],
+    ) {
+        let n = g.len();
+        let (_, comp) = strongly_connected_components(g);
+        for u in 0..n {
+            for v in 0..n {
+                assert_eq!(comp[u] == comp[v], expected[u] == expected[v]);
+            }
+        }
+        for &(u, v) in edges {
+            assert!(comp[u] <= comp[v]);
+        }
+        if let Some(ord) = tsort(g) {
+            let mut pos = vec![0; n];
+            for (i, &u) in ord.iter().enumerate() {
+                pos[u] = i;
+            }
+            assert!(edges.iter().all(|&(u, v)| pos[u] < pos[v]));
+        } else {
+            assert!(edges.iter().any(|&(u, v)| expected[u] == expected[v]));
+        }
+    }
+
+    #[test]
+    fn graph_representations() {
+        let mut rand = Pcg::seed_from_u64(4101);
+        for _ in 0..50 {
+            let n = rand.next_u32() as usize % 12 + 1;
+            let edges: Vec<_> = (0..rand.next_u32() % 20)
+                .map(|_| (rand.next_u32() as usize % n, rand.next_u32() as usize % n))
+                .collect();
+            let mut vv = vec![vec![]; n];
+            for &(u, v) in &edges {
+                vv[u].push(v);
+            }
+            let (_, expected) = strongly_connected_components(&vv);
+            check(&vv, &edges, &expected);
+            check(&vv[..], &edges, &expected);
+            let mut g = graph::Graph::builder(n);
+            g.extend(edges.iter().copied());
+            check(&g.build(), &edges, &expected);
+            let mut g = wip::AdjListBuilder::new(n);
+            g.extend(edges.iter().copied());
+            check(&g.build(), &edges, &expected);
+            check(
+                &AdjList::from_edges(n, edges.iter().copied()),
+                &edges,
+                &expected,
+            );
+
+            let weighted: Vec<_> = edges
+                .iter()
+                .map(|&(u, v)| (u, v, rand.next_u32() as u64 % 10))
+                .collect();
+            let mut g = LabeledGraph::builder(n);
+            g.extend(weighted.iter().copied());
+            let g = g.build();
+            let h = AdjList::from_labeled_edges(n, weighted.iter().copied());
+            assert_eq!(djikstra(&g, 0), djikstra(&h, 0));
+            assert_eq!(djikstra(&g, 0), h.djikstra(0, 0));
+        }
+    }
+}
@@ -1,4 +1,6 @@
-pub trait Tree<'a, T: 'a>: Graph<'a, T> {
+use super::{Graph, GraphExt};
+
+pub trait Tree<'a, T: 'a>: GraphExt<'a, T> {
     fn subtree_vertex_count(&'a self, root: usize) -> Vec<usize> {
         let mut res = vec![1; self.len()];
         let mut stk = vec![root];
@@ -23,9 +25,19 @@ pub trait Tree<'a, T: 'a>: Graph<'a, T> {
     }
     fn diameter(&'a self, v: usize) -> Vec<usize> {
         let dv = self.dist_bfs(v);
-        let s = dv.iter().enumerate().max_by_key(|(_, d)| **d as isize).unwrap().0;
+        let s = dv
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, d)| **d as isize)
+            .unwrap()
+            .0;
         let ds = self.dist_bfs(s);
-        let t = ds.iter().enumerate().max_by_key(|(_, d)| **d as isize).unwrap().0;
+        let t = ds
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, d)| **d as isize)
+            .unwrap()
+            .0;
         let mut path = Vec::with_capacity(ds[t] + 1);
         let mut u = t;
         while u != s {
@@ -41,4 +53,4 @@ pub trait Tree<'a, T: 'a>: Graph<'a, T> {
         path
     }
 }
-impl<'a, T: 'a, G: Graph<'a, T>> Tree<'a, T> for G {}
\ No newline at end of file
+impl<'a, T: 'a, G: Graph<'a, T>> Tree<'a, T> for G {}
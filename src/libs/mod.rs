@@ -1,6 +1,7 @@
 pub mod data_structure;
 pub mod geometory;
 pub mod graph;
+pub mod graph2;
 pub mod io;
 pub mod iterext;
 pub mod macros;
@@ -1 +1,20 @@
-
+// unsigned integers as keys of bitwise structures
+pub trait Unsigned: Copy + Ord {
+    const BITS: usize;
+    fn to_u64(self) -> u64;
+    fn from_u64(x: u64) -> Self;
+}
+macro_rules! unsigned_impl {
+    ($($T:ident)*) => {$(
+        impl Unsigned for $T {
+            const BITS: usize = 8 * std::mem::size_of::<$T>();
+            fn to_u64(self) -> u64 {
+                self as u64
+            }
+            fn from_u64(x: u64) -> Self {
+                x as $T
+            }
+        }
+    )*};
+}
+unsigned_impl!(u8 u16 u32 u64 usize);